@@ -5,7 +5,7 @@ exe export FTID=$ftsub".setalosas.testnet"
 exe export AID=setalosas.testnet
 exe export CID=nft64.setalosas.testnet
 export HI="\\x1b[38;5;208m"
-./redep.sh
+./redep.sh "$@"
 . bal.sh
 # ./tx1.sh
 # . bal.sh
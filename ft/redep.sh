@@ -1,9 +1,12 @@
 #!/bin/bash
 exe() { echo -e "\\x1b[33m+ $@ \x1b[0m" ; "$@" ; }
 eko() { echo -e "$1"$HI"$2" ; }
+# production build, ./redep.sh --dev adds the debug and test methods
+FEATURES=""
+if [ "$1" == "--dev" ]; then FEATURES="--features dev"; fi
 set -e
 eko "🟣" "_____Build contract."
-exe eval "RUSTFLAGS='-C link-arg=-s' cargo build --target wasm32-unknown-unknown --release"
+exe eval "RUSTFLAGS='-C link-arg=-s' cargo build --target wasm32-unknown-unknown --release $FEATURES"
 set +e
 eko "🟤" "_____Copy contract."
 exe cp target/wasm32-unknown-unknown/release/*.wasm ./res/
@@ -30,39 +30,71 @@ use near_sdk::{
     collections::LazyOption,
     env,
     json_types::U128,
-    log, near_bindgen, AccountId, Balance, PanicOnDefault, PromiseOrValue,
+    log, near_bindgen, require, AccountId, Balance, PanicOnDefault, PromiseOrValue,
 };
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
+    owner_id: AccountId,
+    token: FungibleToken,
+    metadata: LazyOption<FungibleTokenMetadata>,
+}
+
+//.rem  state versioning: the version is stored under its own key next to the state,
+//.rem  state without one is V0. migrate() upgrades older layouts to STATE_VERSION.
+const STATE_VERSION: u32 = 1;
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+fn state_version() -> u32 {
+    env::storage_read(STATE_VERSION_KEY).map_or(0, |bytes| {
+        u32::try_from_slice(&bytes).unwrap_or_else(|_| env::panic_str("Invalid state version"))
+    })
+}
+
+fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &STATE_VERSION.to_le_bytes());
+}
+
+//.rem  V0: no owner was stored
+#[derive(BorshDeserialize, BorshSerialize)]
+struct ContractV0 {
     token: FungibleToken,
     metadata: LazyOption<FungibleTokenMetadata>,
 }
 
 const DATA_IMAGE_SVG_NEAR_ICON: &str = "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 288 288'%3E%3Cg id='l' data-name='l'%3E%3Cpath d='M187.58,79.81l-30.1,44.69a3.2,3.2,0,0,0,4.75,4.2L191.86,103a1.2,1.2,0,0,1,2,.91v80.46a1.2,1.2,0,0,1-2.12.77L102.18,77.93A15.35,15.35,0,0,0,90.47,72.5H87.34A15.34,15.34,0,0,0,72,87.84V201.16A15.34,15.34,0,0,0,87.34,216.5h0a15.35,15.35,0,0,0,13.08-7.31l30.1-44.69a3.2,3.2,0,0,0-4.75-4.2L96.14,186a1.2,1.2,0,0,1-2-.91V104.61a1.2,1.2,0,0,1,2.12-.77l89.55,107.23a15.35,15.35,0,0,0,11.71,5.43h3.13A15.34,15.34,0,0,0,216,201.16V87.84A15.34,15.34,0,0,0,200.66,72.5h0A15.35,15.35,0,0,0,187.58,79.81Z'/%3E%3C/g%3E%3C/svg%3E";
 
-const Pink: &str = "\x1b[38;2;255;100;125m";
-const Bright: &str = "\x1b[1m";
-const FgRed: &str = "\x1b[31m";
-const FgGreen: &str = "\x1b[32m";
-const FgBrightGreen: &str = "\x1b[1m\x1b[32m";
-const FgYellow: &str = "\x1b[33m";
-const H: &str = "\x1b[33m";
-const FgBlue: &str = "\x1b[38;5;027m"; //'\x1b[34m";
-const FgLime: &str = "\x1b[38;5;148m"; // '\x1b[35m";
-const FgCyan: &str = "\x1b[38;5;086m"; // '\x1b[36m";
-const FgWhite: &str = "\x1b[37m";
-const FgOrange: &str = "\x1b[38;5;202m";
-const FgPurple: &str = "\x1b[38;5;127m";
-const FgA: &str = "\x1b[38;5;163m"; // magentish
-const FgZ: &str = "\x1b[38;5;039m";
-const FgT: &str = "\x1b[38;5;035m"; // GGB
-const FgX: &str = "\x1b[38;5;042m"; // GGB brighter
+//.rem  ANSI colours for the dev logs, empty strings in production builds
+const fn color(code: &'static str) -> &'static str {
+    if cfg!(feature = "dev") {
+        code
+    } else {
+        ""
+    }
+}
+
+const Pink: &str = color("\x1b[38;2;255;100;125m");
+const Bright: &str = color("\x1b[1m");
+const FgRed: &str = color("\x1b[31m");
+const FgGreen: &str = color("\x1b[32m");
+const FgBrightGreen: &str = color("\x1b[1m\x1b[32m");
+const FgYellow: &str = color("\x1b[33m");
+const H: &str = color("\x1b[33m");
+const FgBlue: &str = color("\x1b[38;5;027m"); //'\x1b[34m";
+const FgLime: &str = color("\x1b[38;5;148m"); // '\x1b[35m";
+const FgCyan: &str = color("\x1b[38;5;086m"); // '\x1b[36m";
+const FgWhite: &str = color("\x1b[37m");
+const FgOrange: &str = color("\x1b[38;5;202m");
+const FgPurple: &str = color("\x1b[38;5;127m");
+const FgA: &str = color("\x1b[38;5;163m"); // magentish
+const FgZ: &str = color("\x1b[38;5;039m");
+const FgT: &str = color("\x1b[38;5;035m"); // GGB
+const FgX: &str = color("\x1b[38;5;042m"); // GGB brighter
 
 //.rem: only these are used
-const R: &str = "\x1b[0m";
-const LightBlue: &str = "\x1b[38;2;150;150;255m";
+const R: &str = color("\x1b[0m");
+const LightBlue: &str = color("\x1b[38;2;150;150;255m");
 
 //.todo: decimals and max supply
 //.todo: NEAR / COTO ratio -> dynamic
@@ -94,10 +126,10 @@ impl Contract {
     #[init]
     pub fn new(owner_id: AccountId, total_supply: U128, metadata: FungibleTokenMetadata) -> Self {
         assert!(!env::state_exists(), "Already initialized");
-        log!("trace 1");
         metadata.assert_valid();
-        log!("trace 2");
+        write_state_version();
         let mut this = Self {
+            owner_id: owner_id.clone(),
             token: FungibleToken::new(b"a".to_vec()),
             metadata: LazyOption::new(b"m".to_vec(), Some(&metadata)),
         };
@@ -112,6 +144,46 @@ impl Contract {
         this
     }
 
+    //.pub  state migration (owner only)
+    //.rem  the contract account itself may always migrate, it is the owner of V0 state
+
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let version = state_version();
+        require!(
+            version != STATE_VERSION,
+            format!("State is already at version {}", version)
+        );
+        let this = match version {
+            0 => {
+                let old: ContractV0 =
+                    env::state_read().unwrap_or_else(|| env::panic_str("No state to migrate"));
+                Self::assert_migrator(&env::current_account_id());
+                Self {
+                    owner_id: env::current_account_id(),
+                    token: old.token,
+                    metadata: old.metadata,
+                }
+            }
+            _ => env::panic_str(&format!("Unknown state version {}", version)),
+        };
+        write_state_version();
+        log!("migrate: state version {} -> {}", version, STATE_VERSION);
+        this
+    }
+
+    fn assert_migrator(owner_id: &AccountId) {
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == *owner_id || caller == env::current_account_id(),
+            "Only the owner can migrate"
+        );
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
     fn on_account_closed(&mut self, account_id: AccountId, balance: Balance) {
         log!("Closed @{} with {}", account_id, balance);
     }
@@ -124,23 +196,30 @@ impl Contract {
 
     #[payable]
     pub fn coto_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        //.rem  gas tracing in dev builds only
+        #[cfg(feature = "dev")]
         let pregas = env::used_gas().0 / 1_000_000_000;
-        let sender_id = env::predecessor_account_id();
+        #[cfg(feature = "dev")]
         let paramLog = format!(
             "FT::coto_transfer: {} -> {} {}_COTO",
-            sender_id, receiver_id, amount.0
+            env::predecessor_account_id(),
+            receiver_id,
+            amount.0
         );
 
         self.token.ft_transfer(receiver_id, amount, memo);
 
-        let postgas = env::used_gas().0 / 1_000_000_000;
-        log!(
-            "{LightBlue}{} // gas: {} + {} = {}{R}",
-            paramLog,
-            pregas,
-            postgas - pregas,
-            postgas
-        );
+        #[cfg(feature = "dev")]
+        {
+            let postgas = env::used_gas().0 / 1_000_000_000;
+            log!(
+                "{LightBlue}{} // gas: {} + {} = {}{R}",
+                paramLog,
+                pregas,
+                postgas - pregas,
+                postgas
+            );
+        }
 
         // orig:
         // assert_one_yocto();
@@ -157,7 +236,12 @@ impl Contract {
         self.token
             .ft_resolve_transfer(sender_id, receiver_id, amount);
     }
+}
 
+//.rem  test entry points, dev builds only
+#[cfg(feature = "dev")]
+#[near_bindgen]
+impl Contract {
     pub fn cross_call_test(&mut self, msg: String) {
         log!("FT cross_call_test called. {}", msg);
     }
@@ -246,4 +330,37 @@ mod tests {
         );
         assert_eq!(contract.ft_balance_of(accounts(1)).0, transfer_amount);
     }
+
+    #[test]
+    fn test_migrate_from_v0() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut token = FungibleToken::new(b"a".to_vec());
+        token.internal_register_account(&accounts(1));
+        token.internal_deposit(&accounts(1), TOTAL_SUPPLY);
+        env::state_write(&ContractV0 {
+            token,
+            metadata: LazyOption::new(b"m".to_vec(), None),
+        });
+
+        testing_env!(context.build());
+        let contract = Contract::migrate();
+        assert_eq!(state_version(), STATE_VERSION);
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can migrate")]
+    fn test_migrate_owner_only() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        env::state_write(&ContractV0 {
+            token: FungibleToken::new(b"a".to_vec()),
+            metadata: LazyOption::new(b"m".to_vec(), None),
+        });
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        Contract::migrate();
+    }
 }
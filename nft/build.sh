@@ -2,5 +2,6 @@
 set -e
 cd "`dirname $0`"/..
 source scripts/flags.sh
+# production build, add --features dev for the debug and test methods
 cargo build --all --target wasm32-unknown-unknown --release
 cp target/wasm32-unknown-unknown/release/*.wasm ./res/
\ No newline at end of file
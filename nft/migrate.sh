@@ -0,0 +1,15 @@
+#!/bin/bash
+exe() { echo -e "\\x1b[33m+ $@ \x1b[0m" ; "$@" ; }
+HI="\\x1b[38;5;208m"
+set -e
+echo -e "🟣"$HI"_____Build contract."
+exe eval "RUSTFLAGS='-C link-arg=-s' cargo build --target wasm32-unknown-unknown --release"
+exe cp target/wasm32-unknown-unknown/release/*.wasm ./res/
+echo -e "🟡"$HI"_____Deploy contract and migrate state."
+exe near deploy $CID --wasmFile res/nft_z2h.wasm --initFunction migrate --initArgs '{}'
+echo -e "🔵"$HI"_____Move NFTs in batches."
+next='"0"'
+while [ "$next" != "null" ]; do
+  next=$(near call $CID migrate_nfts '{"from_index": '$next', "limit": 3}' --accountId $CID --gas 300000000000000 | tail -1)
+  echo "next: $next"
+done
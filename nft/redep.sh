@@ -1,8 +1,11 @@
 #!/bin/bash
 exe() { echo -e "\\x1b[33m+ $@ \x1b[0m" ; "$@" ; }
+# production build, ./redep.sh --dev adds the debug and test methods
+FEATURES=""
+if [ "$1" == "--dev" ]; then FEATURES="--features dev"; fi
 set -e
 echo -e "🟣"$HI"_____Build contract."
-exe eval "RUSTFLAGS='-C link-arg=-s' cargo build --target wasm32-unknown-unknown --release"
+exe eval "RUSTFLAGS='-C link-arg=-s' cargo build --target wasm32-unknown-unknown --release $FEATURES"
 set +e
 echo -e "🟤"$HI"_____Copy contract."
 exe cp target/wasm32-unknown-unknown/release/*.wasm ./res/
@@ -6,5 +6,5 @@ exe export CID=$sub".setalosas.testnet"
 exe export AID=setalosas.testnet
 export HI="\\x1b[38;5;208m"
 
-./redep.sh
+./redep.sh "$@"
 . bal.sh
@@ -0,0 +1,249 @@
+//! Fixed-point money type used for bids, licence prices and payouts.
+//!
+//! `Amount` holds a u128 count of 1e-24 units of the payment token (yocto precision),
+//! the same scale NEAR uses for yoctoNEAR. FT contracts have fewer decimals (COTO has
+//! `COTO_DECIMALS`), so converting to the FT side is an exact multiplication / division by
+//! the yocto units per base unit of the token.
+//! In JSON it travels as a base-10 string of yocto units, like `U128`.
+
+use near_sdk::{
+    borsh::{self, BorshDeserialize, BorshSerialize},
+    json_types::U128,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+
+pub const YOCTO_DECIMALS: u32 = 24;
+pub const COTO_DECIMALS: u8 = 3;
+
+//.rem  decimals has to be at most YOCTO_DECIMALS, set_config checks it for accepted tokens
+fn yocto_per_unit(decimals: u8) -> u128 {
+    10u128.pow(YOCTO_DECIMALS - decimals as u32)
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshDeserialize,
+    BorshSerialize,
+)]
+pub struct Amount(pub u128);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    pub fn from_yocto(yocto: u128) -> Self {
+        Amount(yocto)
+    }
+
+    pub fn yocto(self) -> u128 {
+        self.0
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    //.rem  base units of a token with `decimals` decimals <-> yocto, exact in this direction
+    pub fn from_units(units: U128, decimals: u8) -> Self {
+        Amount(
+            units
+                .0
+                .checked_mul(yocto_per_unit(decimals))
+                .expect("Amount overflow"),
+        )
+    }
+
+    //.rem  truncates anything below the smallest unit of the token
+    pub fn to_units(self, decimals: u8) -> U128 {
+        U128(self.0 / yocto_per_unit(decimals))
+    }
+
+    /// Parses a decimal token amount like "22.5" or "6" into yocto units without rounding.
+    /// Up to 24 decimals are taken, FT amounts with more decimals than the token are rejected
+    /// where they meet the token, see `check_declared_value`.
+    pub fn parse_decimal(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let (int, frac) = match s.split_once('.') {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (s, None),
+        };
+        let digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+        if !digits(int) || !frac.is_none_or(digits) {
+            return Err(format!("invalid amount '{}'", s));
+        }
+        let frac = frac.unwrap_or("");
+        if frac.len() > YOCTO_DECIMALS as usize {
+            return Err(format!(
+                "amount '{}' has more than {} decimals",
+                s, YOCTO_DECIMALS
+            ));
+        }
+        let overflow = || format!("amount '{}' is too large", s);
+        let int: u128 = int.parse().map_err(|_| overflow())?;
+        let fracYocto: u128 = if frac.is_empty() {
+            0
+        } else {
+            frac.parse::<u128>().map_err(|_| overflow())?
+                * 10u128.pow(YOCTO_DECIMALS - frac.len() as u32)
+        };
+        int.checked_mul(10u128.pow(YOCTO_DECIMALS))
+            .and_then(|v| v.checked_add(fracYocto))
+            .map(Amount)
+            .ok_or_else(overflow)
+    }
+
+    /// `self * num / den`, rounded down.
+    pub fn mul_div(self, num: u128, den: u128) -> Self {
+        Amount(
+            self.0
+                .checked_mul(num)
+                .expect("Amount overflow")
+                .checked_div(den)
+                .expect("Amount division by zero"),
+        )
+    }
+
+    /// Percentage share of `self`, rounded down.
+    pub fn percent(self, pt: u128) -> Self {
+        self.mul_div(pt, 100)
+    }
+
+    /// Splits `self` into `parts` equal amounts, rounded down.
+    pub fn div_parts(self, parts: u128) -> Self {
+        Amount(self.0.checked_div(parts).expect("Amount division by zero"))
+    }
+
+    pub fn times(self, n: u128) -> Self {
+        Amount(self.0.checked_mul(n).expect("Amount overflow"))
+    }
+}
+
+impl Add for Amount {
+    type Output = Amount;
+    fn add(self, rhs: Amount) -> Amount {
+        Amount(self.0.checked_add(rhs.0).expect("Amount overflow"))
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, rhs: Amount) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Amount {
+    type Output = Amount;
+    fn sub(self, rhs: Amount) -> Amount {
+        Amount(self.0.checked_sub(rhs.0).expect("Amount underflow"))
+    }
+}
+
+impl SubAssign for Amount {
+    fn sub_assign(&mut self, rhs: Amount) {
+        *self = *self - rhs;
+    }
+}
+
+impl std::iter::Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Amount {
+        iter.fold(Amount::ZERO, |acc, a| acc + a)
+    }
+}
+
+//.rem  human readable, for logs: 22.5 instead of 22500000000000000000000000
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unit = 10u128.pow(YOCTO_DECIMALS);
+        let int = self.0 / unit;
+        let frac = self.0 % unit;
+        if frac == 0 {
+            write!(f, "{}", int)
+        } else {
+            let frac = format!("{:024}", frac);
+            write!(f, "{}.{}", int, frac.trim_end_matches('0'))
+        }
+    }
+}
+
+impl From<U128> for Amount {
+    fn from(v: U128) -> Self {
+        Amount(v.0)
+    }
+}
+
+impl From<Amount> for U128 {
+    fn from(v: Amount) -> Self {
+        U128(v.0)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(&U128(self.0), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <U128 as Deserialize>::deserialize(deserializer).map(Amount::from)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 1_000_000_000_000_000_000_000_000;
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!(Amount::parse_decimal("6").unwrap(), Amount(6 * ONE));
+        assert_eq!(
+            Amount::parse_decimal("22.5").unwrap(),
+            Amount(22 * ONE + ONE / 2)
+        );
+        assert_eq!(
+            Amount::parse_decimal("0.000000000000000000000001").unwrap(),
+            Amount(1)
+        );
+        assert!(Amount::parse_decimal("").is_err());
+        assert!(Amount::parse_decimal("1.").is_err());
+        assert!(Amount::parse_decimal("-1").is_err());
+        assert!(Amount::parse_decimal("1e5").is_err());
+        assert!(Amount::parse_decimal("0.0000000000000000000000001").is_err());
+    }
+
+    #[test]
+    fn test_display_and_units() {
+        let a = Amount::parse_decimal("22.25").unwrap();
+        assert_eq!(a.to_string(), "22.25");
+        assert_eq!(a.to_units(COTO_DECIMALS), U128(22_250));
+        assert_eq!(Amount::from_units(U128(22_250), COTO_DECIMALS), a);
+        assert_eq!(a.to_units(6), U128(22_250_000));
+        assert_eq!(Amount::from_units(U128(22_250_000), 6), a);
+        assert_eq!(a.to_units(0), U128(22));
+        assert_eq!(
+            near_sdk::serde_json::to_string(&a).unwrap(),
+            format!("\"{}\"", 22 * ONE + ONE / 4)
+        );
+    }
+
+    #[test]
+    fn test_split_adds_up() {
+        let value = Amount::parse_decimal("10").unwrap();
+        let each = value.div_parts(3);
+        let rest = value - each.times(3);
+        assert_eq!(each.times(3) + rest, value);
+        let treasury = each.percent(10);
+        assert_eq!((each - treasury) + treasury, each);
+    }
+}
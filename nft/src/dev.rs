@@ -0,0 +1,235 @@
+//! Debug and test entry points, only built with the `dev` feature (`cargo build --features dev`).
+//!
+//! The `test_*` methods bid and buy on the `testContentData` fixtures, `inc_cnt`, `add_cnt` and
+//! `modcont` poke the counter and the contents, the `show*` methods and `spectrum` print the
+//! contract state and the colours to the logs. Production builds have none of these, their logs
+//! carry no colour codes and no gas tracing.
+
+use crate::*;
+
+const testContentData: [(&str, &str, u64); 5] = [
+    (
+        "85d491b3-18f8-40f6-be33-b83dd749a8a4",
+        "kremilek.testnet",
+        125000000,
+    ),
+    (
+        "9d724602-caa5-4aec-9fe3-6f1f5c08231b",
+        "donatello.testnet",
+        126000000,
+    ),
+    (
+        "770d7dd3-8c33-4834-9ba9-b5499a4b2e85",
+        "kremilek.testnet",
+        127000000,
+    ),
+    (
+        "5e7668b5-015b-4392-970d-aaff3d0091e2",
+        "vochomurka.testnet",
+        133000000,
+    ),
+    (
+        "b82501f5-edb7-4e2f-a055-7c31b036f433",
+        "setalosas.testnet",
+        157000000,
+    ),
+];
+
+#[near_bindgen]
+impl Contract {
+    pub fn inc_cnt(&mut self) {
+        self.assert_role(Role::Operator, "change the counter");
+        self.cnt += 1;
+    }
+    pub fn add_cnt(&mut self, cnt: u32) {
+        self.assert_role(Role::Operator, "change the counter");
+        self.cnt += cnt;
+    }
+
+    //.rem  points every content at token 2000, to try the dash views on broken records
+    pub fn modcont(&mut self) {
+        self.assert_role(Role::Admin, "modify contents");
+        let keys: Vec<String> = self.contents.keys().collect();
+        for key in keys {
+            let mut contentRec = self.load_content(&key).unwrap();
+            contentRec.tokenId = 2000;
+            self.contents
+                .insert(&key, &VersionedContentRec::V1(contentRec));
+        }
+    }
+
+    pub fn spectrum() {
+        log!("{FgRed}: FgRed ======= testX{}", R);
+        log!("{FgGreen}: FgGreen ======= testX{}", R);
+        log!("{FgBrightGreen}: FgBrightGreen ======= testX{}", R);
+        log!("{FgYellow}: FgYellow ======= testX{}", R);
+        log!("{H}: H ======= testX{}", R);
+        log!("{FgBlue}: FgBlue ======= testX{}", R);
+        log!("{FgLime}: FgLime ======= testX{}", R);
+        log!("{FgCyan}: FgCyan ======= testX{}", R);
+        log!("{FgWhite}: FgWhite ======= testX{}", R);
+        log!("{R}: R ======= testX{}", R);
+        log!("{FgOrange}: FgOrange ======= testX{}", R);
+        log!("{FgPurple}: FgPurple ======= testX{}", R);
+        log!("{FgA}: FgA ======= testX{}", R);
+        log!("{FgZ}: FgZ ======= testX{}", R);
+        log!("{FgT}: FgT ======= testX{}", R);
+        log!("{FgX}: FgX ======= testX{}", R);
+    }
+
+    pub(crate) fn print_env(&self, pre: &str) {
+        log!(
+            "{pre}env::current_account_id(): {}{R}",
+            env::current_account_id()
+        );
+        log!(
+            "{pre}env::account_balance: {} NEAR{R}",
+            Amount::from_yocto(env::account_balance())
+        );
+        log!(
+            "{pre}env::signer_account_id: {}{R}",
+            env::signer_account_id()
+        );
+        log!(
+            "{pre}env::predecessor_account_id: {}{R}",
+            env::predecessor_account_id()
+        );
+        log!(
+            "{pre}env::attached_deposit: {} NEAR{R}",
+            Amount::from_yocto(env::attached_deposit())
+        );
+        log!("{pre}{}{R}", ugas());
+        log!(
+            "{pre}env::prepaid_gas: {} G{R}",
+            env::prepaid_gas().0 / 1_000_000_000
+        );
+        log!("{pre}env::storage_usage: {}{R}", env::storage_usage());
+    }
+
+    fn showContentInfo(&self, msg: &str, contentId: &str, contentRec: &ContentRec) {
+        log!("{FgX}{msg}: {}{R}", contentId);
+    }
+
+    fn _showContentList(&self, withBidding: bool) {
+        log!("{FgYellow}Current content list:{}", R);
+        for (contentKeyKey, contentRec) in self.contents.iter() {
+            let contentRec = contentRec.into_current();
+            self.showContentInfo("Content", &contentKeyKey, &contentRec);
+            if withBidding {
+                if contentRec.tokenId > 0 {
+                    let scoutList = self.get_nft_owners_for(contentRec.tokensArr.clone());
+
+                    for (i, scout) in scoutList.iter().enumerate() {
+                        log!(
+                            "{FgT}Slot[{i}]: ${} #{} for {}{R}",
+                            contentRec.bidvalArr[i],
+                            tokenId2Str(contentRec.tokensArr[i]),
+                            scout
+                        );
+                    }
+                } else {
+                    log!("No bids for {}", contentKeyKey);
+                }
+            }
+        }
+    }
+
+    pub fn showContentList(&self) {
+        self._showContentList(false)
+    }
+
+    pub fn showContentListWithBidding(&self) {
+        self._showContentList(true)
+    }
+
+    pub fn show_content_nfts(&self) {
+        let tokens = self.tokens.nft_tokens(None, Some(1000));
+        for token in tokens {
+            let meta = token.metadata.unwrap();
+            log!(
+                "{FgOrange}#{} {FgCyan}{} {R}{} {FgGreen}{}{R}",
+                token.token_id,
+                token.owner_id.to_string(),
+                meta.title.unwrap(),
+                meta.media.unwrap()
+            );
+        }
+    }
+
+    pub fn show_licence_nfts(&self) {
+        let tokens = self.licenceTokens.nft_tokens(None, Some(1000));
+        for token in tokens {
+            let meta = token.metadata.unwrap();
+            log!(
+                "{FgOrange}#{} {FgCyan}{} {R}{} {FgGreen}{}{R}",
+                token.token_id,
+                token.owner_id.to_string(),
+                meta.title.unwrap(),
+                meta.media.unwrap()
+            );
+        }
+    }
+
+    pub fn show_nfts(&self) {
+        self.show_content_nfts();
+        self.show_licence_nfts();
+    }
+
+    #[payable]
+    pub fn test_buy(&mut self, ix: usize, price: Amount) {
+        self.assert_role(Role::Operator, "use the test methods");
+        let cont = testContentData[ix];
+        self.buy_licence(
+            String::from(cont.0),
+            String::from(cont.1),
+            cont.2,
+            String::from(env::predecessor_account_id()),
+            price,
+        );
+        // self.showContentListWithBidding();
+    }
+    #[payable]
+    pub fn test_bid(&mut self, ix: usize, value: Amount, pt: i32) {
+        self.assert_role(Role::Operator, "use the test methods");
+        let content = testContentData[ix];
+        let scoutId = String::from(env::predecessor_account_id());
+        let token = self.default_token();
+        let unused = self.add_bid_coto(
+            String::from(content.0),
+            String::from(content.1),
+            content.2,
+            scoutId.clone(),
+            value,
+            pt,
+            &token,
+        );
+        self.credit_earnings(&scoutId, &token, unused);
+        self.showContentListWithBidding();
+    }
+
+    #[payable]
+    pub fn test_bid2(&mut self, ix: usize, value: Amount, pt: i32) {
+        self.assert_role(Role::Operator, "use the test methods");
+        let content = testContentData[ix];
+        let scoutId = String::from(env::signer_account_id());
+        let token = self.default_token();
+        let unused = self.add_bid_coto(
+            String::from(content.0),
+            String::from(content.1),
+            content.2,
+            scoutId.clone(),
+            value,
+            pt,
+            &token,
+        );
+        self.credit_earnings(&scoutId, &token, unused);
+        self.showContentListWithBidding();
+    }
+}
+
+fn ugas() -> String {
+    format!("gasUsed: {} G ", env::used_gas().0 / 1_000_000_000)
+}
+fn gas_log(msg: &str) {
+    log!("{Pink}{msg}{}{R}", ugas());
+}
@@ -0,0 +1,222 @@
+//! Events logged by the contract, in the NEP-297 format:
+//! `EVENT_JSON:{"standard":"cmg","version":"1.0.0","event":"content_bid","data":{..}}`
+//!
+//! Every `data` object carries the `timestamp` (block timestamp in ns, as a string) of the
+//! block the event happened in. Amounts are yocto strings like every `Amount` in JSON.
+//! Indexers should check `standard` and `version` before reading `data`.
+
+use crate::amount::Amount;
+use crate::{Config, ContentStatus, PauseScope, Role};
+use near_sdk::json_types::U64;
+use near_sdk::{env, serde_json, AccountId};
+use serde::Serialize;
+
+pub const EVENT_STANDARD: &str = "cmg";
+pub const EVENT_VERSION: &str = "1.0.0";
+
+//.rem  one entry per owner of the content NFTs, the creator included
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BidShare {
+    pub owner: String,
+    pub value: Amount,
+    pub percentage: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundReason {
+    UnusedBid,        // the whole token units of a bid that won no slots, returned by the FT
+    Paused,           // the whole transfer, bids or licences were paused
+    OutbidInTransfer, // outbid in an nft_transfer_call, held until it resolved
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutReason {
+    BidCreator,
+    BidTreasury,
+    LicenceOwner,
+    LicenceTreasury,
+    BidRemainder, // the part of an unused bid below the smallest unit of the token
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    Transferred,
+    Recredited,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum CmgEvent {
+    //.rem  the state of the content after a bid
+    ContentBid {
+        content_id: String,
+        token: String,
+        slots: usize,
+        slot_pt: u32,
+        creator_pt: u32,
+        bids: Vec<BidShare>,
+    },
+    //.rem  a slot changed hands, the refund is credited to the previous owner
+    //.rem  (held until the transfer resolves if the slot was in an nft_transfer_call)
+    Outbid {
+        content_id: String,
+        token_id: String,
+        previous_owner: AccountId,
+        new_owner: AccountId,
+        refund: Amount,
+        token: String,
+    },
+    Refund {
+        account_id: AccountId,
+        token: String,
+        amount: Amount,
+        reason: RefundReason,
+    },
+    Licence {
+        content_id: String,
+        token_id: String,
+        buyer: AccountId,
+        price: Amount,
+        token: String,
+    },
+    //.rem  a share credited to the earnings of the account
+    Payout {
+        content_id: String,
+        account_id: String,
+        token: String,
+        amount: Amount,
+        reason: PayoutReason,
+    },
+    EarningsClaimed {
+        account_id: String,
+        token: String,
+        amount: Amount,
+    },
+    TransferFundsResult {
+        account_id: String,
+        token: String,
+        amount: Amount,
+        status: TransferStatus,
+    },
+    ConfigChanged {
+        old: Config,
+        new: Config,
+    },
+    ContentSettled {
+        content_id: String,
+        bidding_end: U64,
+    },
+    ContentStatusChanged {
+        content_id: String,
+        from: ContentStatus,
+        to: ContentStatus,
+        by: AccountId,
+    },
+    RoleGranted {
+        account_id: AccountId,
+        role: Role,
+        by: AccountId,
+    },
+    RoleRevoked {
+        account_id: AccountId,
+        role: Role,
+        by: AccountId,
+    },
+    PauseChanged {
+        paused: Vec<PauseScope>,
+        by: AccountId,
+    },
+    SettlementStarted {
+        content_id: String,
+        token_ids: Vec<String>,
+    },
+    SettlementEnded {
+        content_id: String,
+        token_ids: Vec<String>,
+    },
+    SettlementCleared {
+        content_id: String,
+        token_ids: Vec<String>,
+    },
+}
+
+impl CmgEvent {
+    /// The log line of the event, `timestamp` is added to its data.
+    pub fn to_log(&self, timestamp: u64) -> String {
+        let mut event = serde_json::to_value(self).expect("CmgEvent is always serializable");
+        event["standard"] = EVENT_STANDARD.into();
+        event["version"] = EVENT_VERSION.into();
+        event["data"]["timestamp"] = serde_json::json!(U64(timestamp));
+        format!("EVENT_JSON:{}", event)
+    }
+
+    pub fn emit(&self) {
+        env::log_str(&self.to_log(env::block_timestamp()));
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    fn parse(log: &str) -> serde_json::Value {
+        serde_json::from_str(log.strip_prefix("EVENT_JSON:").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_envelope() {
+        let event = CmgEvent::Refund {
+            account_id: "scout.testnet".parse().unwrap(),
+            token: "coto.testnet".into(),
+            amount: Amount::parse_decimal("2.5").unwrap(),
+            reason: RefundReason::UnusedBid,
+        };
+        let log = parse(&event.to_log(1_650_000_000_000_000_000));
+        assert_eq!(log["standard"], "cmg");
+        assert_eq!(log["version"], "1.0.0");
+        assert_eq!(log["event"], "refund");
+        assert_eq!(
+            log["data"],
+            serde_json::json!({
+                "account_id": "scout.testnet",
+                "token": "coto.testnet",
+                "amount": "2500000000000000000000000",
+                "reason": "unused_bid",
+                "timestamp": "1650000000000000000",
+            })
+        );
+    }
+
+    #[test]
+    fn test_event_names() {
+        let event = CmgEvent::ContentBid {
+            content_id: "c1:creator.testnet:1".into(),
+            token: "coto.testnet".into(),
+            slots: 2,
+            slot_pt: 2,
+            creator_pt: 96,
+            bids: vec![BidShare {
+                owner: "scout.testnet".into(),
+                value: Amount::ZERO,
+                percentage: 4,
+            }],
+        };
+        let log = parse(&event.to_log(7));
+        assert_eq!(log["event"], "content_bid");
+        assert_eq!(log["data"]["bids"][0]["percentage"], 4);
+        assert_eq!(log["data"]["timestamp"], "7");
+
+        let event = CmgEvent::TransferFundsResult {
+            account_id: "scout.testnet".into(),
+            token: "near".into(),
+            amount: Amount::ZERO,
+            status: TransferStatus::Recredited,
+        };
+        let log = parse(&event.to_log(7));
+        assert_eq!(log["event"], "transfer_funds_result");
+        assert_eq!(log["data"]["status"], "recredited");
+    }
+}
@@ -22,6 +22,7 @@ NOTES:
 #![allow(unused_imports)]
 
 use near_contract_standards::non_fungible_token::{
+    core::{NonFungibleTokenCore, NonFungibleTokenResolver},
     metadata::{
         NFTContractMetadata, NonFungibleTokenMetadataProvider, TokenMetadata, NFT_METADATA_SPEC,
     },
@@ -29,13 +30,14 @@ use near_contract_standards::non_fungible_token::{
 };
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::LazyOption,
+    collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet},
     env, ext_contract,
-    json_types::{Base64VecU8, U128},
+    json_types::{Base64VecU8, U128, U64},
     near_bindgen, require,
     serde_json::json,
     utils::assert_one_yocto,
-    AccountId, BorshStorageKey, Gas, PanicOnDefault, Promise, PromiseOrValue,
+    AccountId, BorshStorageKey, CryptoHash, CurveType, Gas, PanicOnDefault, Promise,
+    PromiseOrValue, PromiseResult, PublicKey,
 }; //.todo: temp, remove later
 
 use serde::ser::{Serialize, SerializeStruct, Serializer};
@@ -43,22 +45,39 @@ use std::collections::HashMap;
 
 const DEBUG_LOG: bool = true;
 
+//.rem  trace logs, dev builds only (the 100 logs of a receipt are soon used up)
+//.rem  events are always logged, with CmgEvent::emit
 #[macro_export]
 macro_rules! log {
   ($($arg:tt)*) => {
-    if DEBUG_LOG {
-      println!($($arg)*);
-    } else {
-      near_sdk::log!(format!($($arg)*).as_str());
+    if cfg!(feature = "dev") {
+      if DEBUG_LOG {
+        println!($($arg)*);
+      } else {
+        near_sdk::log!(format!($($arg)*).as_str());
+      }
     }
   };
 }
 
 // pub mod external;
 // pub use crate::external::*;
-
-pub const COTO_GAS: Gas = Gas(2_000_000_000_000);
+pub mod amount;
+pub use crate::amount::*;
+pub mod events;
+pub mod message;
+
+#[cfg(feature = "dev")]
+mod dev;
+use crate::events::{BidShare, CmgEvent, PayoutReason, RefundReason, TransferStatus};
+pub use crate::message::{ContentApproval, TransferMessage};
+mod migration;
+
+pub const FT_TRANSFER_GAS: Gas = Gas(10_000_000_000_000); // NEP-141 ft_transfer, any accepted token
 pub const REMAIN_GAS: Gas = Gas(10_000_000_000_000);
+pub const RESOLVE_GAS: Gas = Gas(5_000_000_000_000);
+pub const UPGRADE_GAS: Gas = Gas(30_000_000_000_000); // kept by upgrade itself, the rest goes to migrate
+pub const MIN_MIGRATE_GAS: Gas = Gas(50_000_000_000_000); // upgrade refuses to start migrate with less
 #[ext_contract(ext_contract)]
 trait ExtContract {
     fn cross_call_test(&self);
@@ -67,13 +86,31 @@ trait ExtContract {
 
 #[ext_contract(ext_self)]
 pub trait ExtSelf {
-    fn callback();
+    fn resolve_transfer_funds(&mut self, accountId: String, token: String, amount: Amount) -> bool;
     //fn on_buy_service(service_id: u64) -> Service;
 }
 
-const BIDPT: usize = 20;
+//.rem  slot layout used when content is created implicitly by a bid or a buy
+const DEFAULT_SLOT_COUNT: usize = 20;
+const DEFAULT_SLOT_PT: u32 = 1;
+const MAX_SLOT_COUNT: usize = 99; // token ids are allocated in blocks of 100 per content
+const DASH_PAGE_SIZE: u64 = 50;
+//.rem  content metadata is copied into the metadata of every content NFT, so it is kept short
+const MAX_TITLE_LEN: usize = 128;
+const MAX_DESCRIPTION_LEN: usize = 1024;
+const MAX_URI_LEN: usize = 512; // media and reference
+const LICENCE_TOKEN_ID_BASE: usize = 1_000_000; // licence ids count up from here, content ids stay below
+const DEFAULT_SNIPE_MINUTES: u32 = 10;
+const DEFAULT_MIN_INCREMENT: Amount = Amount(E24 / 100); // 0.01 COTO per slot
+const DEFAULT_MIN_INCREMENT_PT: u32 = 5;
+const NS_PER_MINUTE: u64 = 60_000_000_000;
 const E24: u128 = 1_000_000_000_000_000_000_000_000;
-const TREASURY_ID: &str = "botticelli.testnet";
+const DEFAULT_TREASURY_ID: &str = "botticelli.testnet";
+const DEFAULT_COTO_ID: &str = "ft1.setalosas.testnet";
+const NEAR_TOKEN: &str = "near"; // earnings token id for native NEAR, FTs use their contract id
+const DEFAULT_TREASURY_PT: u32 = 10;
+const DEFAULT_MIN_LICENCE_PRICE: Amount = Amount(E24 / 20); // 0.05
+const DEFAULT_LICENCE_PRICE_PT: u32 = 100; // 1.0 * sum of the bids
 const DEFAULT_TOKEN_METADATA: TokenMetadata = TokenMetadata {
     title: None,
     description: None,
@@ -88,52 +125,304 @@ const DEFAULT_TOKEN_METADATA: TokenMetadata = TokenMetadata {
     reference: None,
     reference_hash: None,
 };
-const testContentData: [(&str, &str, u64); 5] = [
-    (
-        "85d491b3-18f8-40f6-be33-b83dd749a8a4",
-        "kremilek.testnet",
-        125000000,
-    ),
-    (
-        "9d724602-caa5-4aec-9fe3-6f1f5c08231b",
-        "donatello.testnet",
-        126000000,
-    ),
-    (
-        "770d7dd3-8c33-4834-9ba9-b5499a4b2e85",
-        "kremilek.testnet",
-        127000000,
-    ),
-    (
-        "5e7668b5-015b-4392-970d-aaff3d0091e2",
-        "vochomurka.testnet",
-        133000000,
-    ),
-    (
-        "b82501f5-edb7-4e2f-a055-7c31b036f433",
-        "setalosas.testnet",
-        157000000,
-    ),
-];
 
 #[derive(Debug, BorshDeserialize, BorshSerialize, serde::Serialize)]
 pub struct ContentKey {
     //.rem: ContentKey___________________________
     key: String,
     contentId: String,
-    creatorId: String,
+    creatorId: AccountId,
     timestamp: u64,
 }
 
-#[derive(Debug, Default, BorshDeserialize, BorshSerialize, serde::Serialize)]
+#[derive(Debug, Default, Clone, BorshDeserialize, BorshSerialize, serde::Serialize)]
 pub struct ContentRec {
     //.rem: ContentRec___________________________
     creatorId: String,
     contentId: String,
     timestamp: u64,
-    bidvalArr: [f32; BIDPT],
-    tokensArr: [usize; BIDPT],
+    slotPt: u32,    // size of one auctioned slot in percent
+    creatorPt: u32, // kept by the creator token: 100 - slots * slotPt
+    bidvalArr: Vec<Amount>,
+    tokensArr: Vec<usize>,
     tokenId: usize,
+    biddingStart: Option<U64>, // block timestamp (ns), no window -> bidding never closes
+    biddingEnd: Option<U64>,
+    snipeMinutes: u32, // a bid this close to biddingEnd pushes it out by the same amount
+    settled: bool,     // window closed and settled, slots and values are frozen
+    minIncrement: Amount, // a slot is only taken by at least its value + the larger of these
+    minIncrementPt: u32,
+    bidToken: Option<String>, // FT the bids are paid in, set by the first winning bid
+    metadata: ContentMetadata,
+    status: ContentStatus,
+}
+
+//.rem  Draft: not listed yet, Open: bids and licences, Closed: licences only,
+//.rem  Delisted: nothing (taken down, only an admin can list it again), Archived: final
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum ContentStatus {
+    Draft,
+    #[default]
+    Open,
+    Closed,
+    Delisted,
+    Archived,
+}
+
+impl ContentStatus {
+    fn can_become(self, to: ContentStatus) -> bool {
+        use ContentStatus::*;
+        matches!(
+            (self, to),
+            (Draft, Open)
+                | (Draft, Delisted)
+                | (Open, Draft)
+                | (Open, Closed)
+                | (Open, Delisted)
+                | (Closed, Open)
+                | (Closed, Delisted)
+                | (Closed, Archived)
+                | (Delisted, Open)
+                | (Delisted, Closed)
+                | (Delisted, Archived)
+        )
+    }
+
+    fn is_licensable(self) -> bool {
+        matches!(self, ContentStatus::Open | ContentStatus::Closed)
+    }
+}
+
+//.rem  set by the creator, the content and licence NFT metadata is generated from it
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    BorshDeserialize,
+    BorshSerialize,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ContentMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,          // URI of the content
+    pub mediaHash: Option<Base64VecU8>, // sha256 of what media points to
+    pub reference: Option<String>,      // URI of a JSON file with more info
+}
+
+impl ContentMetadata {
+    fn assert_valid(&self) {
+        for (name, value, max) in [
+            ("Title", &self.title, MAX_TITLE_LEN),
+            ("Description", &self.description, MAX_DESCRIPTION_LEN),
+            ("Media", &self.media, MAX_URI_LEN),
+            ("Reference", &self.reference, MAX_URI_LEN),
+        ] {
+            if let Some(value) = value {
+                require!(
+                    value.len() <= max,
+                    format!("{} must be at most {} bytes", name, max)
+                );
+            }
+        }
+        if let Some(mediaHash) = &self.mediaHash {
+            require!(mediaHash.0.len() == 32, "Media hash must be 32 bytes");
+        }
+    }
+}
+
+//.rem  content records are upgraded when read, a layout change adds a variant and a legacy struct
+//.rem  V0 records are moved over by migrate as they are
+#[derive(Debug, BorshDeserialize, BorshSerialize)]
+pub enum VersionedContentRec {
+    V0(migration::ContentRecV0),
+    V1(ContentRec),
+}
+
+impl VersionedContentRec {
+    fn into_current(self) -> ContentRec {
+        match self {
+            VersionedContentRec::V0(contentRec) => contentRec.into_current(),
+            VersionedContentRec::V1(contentRec) => contentRec,
+        }
+    }
+}
+
+impl ContentRec {
+    //.rem  (increment, minimum next bid) for a slot currently held at `current`
+    fn min_outbid(&self, current: Amount) -> (Amount, Amount) {
+        let increment = std::cmp::max(
+            self.minIncrement,
+            current.percent(self.minIncrementPt as u128),
+        );
+        (increment, current + increment)
+    }
+}
+
+struct BidPlan {
+    bidLimit: Amount,
+    wonSlots: Vec<usize>, // indexes into bidvalArr, cheapest first
+    usedUpValue: Amount,
+    minIncrement: Amount,
+    minNextBid: Amount,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SlotQuote {
+    slot: usize,
+    tokenId: Option<usize>, // none until the content NFTs are minted by the first bid
+    previousValue: Amount,
+    previousOwner: Option<String>, // displaced scout, none if the slot had no bid yet
+    price: Amount,
+    creatorShare: Amount,
+    treasuryShare: Amount,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct BidQuote {
+    slots: Vec<SlotQuote>,
+    percentWon: u32,
+    pricePerSlot: Amount,
+    used: Amount,
+    refund: Amount, // credited back to the scout
+    creatorShare: Amount,
+    treasuryShare: Amount,
+    minIncrement: Amount,
+    minNextBid: Amount, // for the cheapest slot
+}
+
+//.rem  platform parameters, owner editable with set_config
+#[derive(
+    Debug, Clone, PartialEq, BorshDeserialize, BorshSerialize, serde::Serialize, serde::Deserialize,
+)]
+pub struct Config {
+    treasuryId: AccountId,
+    acceptedTokens: Vec<AcceptedToken>, // FT contracts bids and licences can be paid with, the first is the default
+    treasuryPt: u32, // of licence prices and of what a bid pays over the displaced value
+    minLicencePrice: Amount, // for content nobody has bid on yet
+    licencePricePt: u32, // minimum licence price in percent of the sum of the bids
+    platformIds: Vec<AccountId>, // may register content for a creator with the creator's signature
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            treasuryId: AccountId::new_unchecked(DEFAULT_TREASURY_ID.to_string()),
+            acceptedTokens: vec![AcceptedToken {
+                tokenId: AccountId::new_unchecked(DEFAULT_COTO_ID.to_string()),
+                decimals: COTO_DECIMALS,
+            }],
+            treasuryPt: DEFAULT_TREASURY_PT,
+            minLicencePrice: DEFAULT_MIN_LICENCE_PRICE,
+            licencePricePt: DEFAULT_LICENCE_PRICE_PT,
+            platformIds: Vec::new(),
+        }
+    }
+}
+
+//.rem  the decimals of the FT contract (its ft_metadata), amounts are converted with them
+#[derive(
+    Debug, Clone, PartialEq, BorshDeserialize, BorshSerialize, serde::Serialize, serde::Deserialize,
+)]
+pub struct AcceptedToken {
+    tokenId: AccountId,
+    decimals: u8,
+}
+
+//.rem  each role includes the lower ones: Owner > Admin > Guardian > Operator
+//.rem  the owner is the owner_id of the NFT collections, the others are granted
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    BorshDeserialize,
+    BorshSerialize,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum Role {
+    Operator, // counters and test methods
+    Guardian, // pause
+    Admin,    // content moderation, minting, unpause, operators and guardians
+    Owner,    // config, upgrades, admins
+}
+
+impl Role {
+    fn holder(self) -> &'static str {
+        match self {
+            Role::Operator => "an operator",
+            Role::Guardian => "a guardian",
+            Role::Admin => "an admin",
+            Role::Owner => "the owner",
+        }
+    }
+
+    //.rem  the role that grants and revokes this one
+    fn manager(self) -> Role {
+        match self {
+            Role::Operator | Role::Guardian => Role::Admin,
+            _ => Role::Owner,
+        }
+    }
+}
+
+//.rem  what pause stops: bids, licence sales and transfers of the content NFTs
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    BorshDeserialize,
+    BorshSerialize,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum PauseScope {
+    Bids,
+    Licences,
+    Transfers,
+}
+
+impl PauseScope {
+    const ALL: [PauseScope; 3] = [
+        PauseScope::Bids,
+        PauseScope::Licences,
+        PauseScope::Transfers,
+    ];
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct LicencePayout {
+    ownerId: String,
+    pt: i32,
+    amount: Amount,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct LicenceQuote {
+    minPrice: Amount,
+    price: Amount,
+    token: Option<String>, // the licence has to be paid in this token, none: any accepted one
+    treasuryFee: Amount,
+    payouts: Vec<LicencePayout>, // largest share first
 }
 
 #[derive(Debug)] //.fix: cleanup these
@@ -154,20 +443,60 @@ pub struct Contract {
     metadata: LazyOption<NFTContractMetadata>,
     licenceMetadata: LazyOption<NFTContractMetadata>,
     cnt: u32,
-    contents: HashMap<String, ContentRec>, //.fix  HashMap vs UnorderedMap
-    emptyContentRec: ContentRec,
-    lock: u32, //.fix  this should be in contentRec
+    contents: UnorderedMap<String, VersionedContentRec>,
+    earnings: LookupMap<String, HashMap<String, Amount>>, // account -> token -> claimable
+    config: Config,
+    creatorKeys: LookupMap<AccountId, PublicKey>, // verify the content approvals of platforms
+    roles: UnorderedMap<AccountId, Role>,         // granted admins, guardians and operators
+    paused: Vec<PauseScope>,
+    settlements: UnorderedMap<String, Settlement>, // content key -> content NFTs in flight
+    tokenDecimals: LookupMap<String, u8>, // every token ever accepted, its earnings stay claimable
+    licencesPerOwner: LookupMap<AccountId, UnorderedSet<TokenId>>, // licenceTokens has no enumeration
+}
+
+//.rem  content NFTs in an nft_transfer_call, their owners are only final after
+//.rem  nft_resolve_transfer. Bids and licences go on meanwhile, an NFT outbid in flight
+//.rem  goes to the bidder and its refund is held until the transfer resolves.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, serde::Serialize)]
+pub struct Settlement {
+    tokenIds: Vec<TokenId>, // one entry per transfer in flight, nested ones repeat the token
+    since: U64,             // block timestamp of the first transfer
+    held: Vec<HeldRefund>,  // outbid refunds of NFTs in flight
+}
+
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, serde::Serialize)]
+pub struct HeldRefund {
+    tokenId: TokenId,
+    ownerId: AccountId, // the receiver the NFT was outbid from
+    token: String,
+    amount: Amount,
 }
 
 const DATA_IMAGE_SVG_NEAR_ICON: &str = "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 288 288'%3E%3Cg id='l' data-name='l'%3E%3Cpath d='M187.58,79.81l-30.1,44.69a3.2,3.2,0,0,0,4.75,4.2L191.86,103a1.2,1.2,0,0,1,2,.91v80.46a1.2,1.2,0,0,1-2.12.77L102.18,77.93A15.35,15.35,0,0,0,90.47,72.5H87.34A15.34,15.34,0,0,0,72,87.84V201.16A15.34,15.34,0,0,0,87.34,216.5h0a15.35,15.35,0,0,0,13.08-7.31l30.1-44.69a3.2,3.2,0,0,0-4.75-4.2L96.14,186a1.2,1.2,0,0,1-2-.91V104.61a1.2,1.2,0,0,1,2.12-.77l89.55,107.23a15.35,15.35,0,0,0,11.71,5.43h3.13A15.34,15.34,0,0,0,216,201.16V87.84A15.34,15.34,0,0,0,200.66,72.5h0A15.35,15.35,0,0,0,187.58,79.81Z'/%3E%3C/g%3E%3C/svg%3E";
 
 #[derive(BorshSerialize, BorshStorageKey)]
 enum StorageKey {
-    NonFungibleToken,
+    NonFungibleToken, // V0, shared by both collections
     Metadata,
-    TokenMetadata,
-    Enumeration,
-    Approval,
+    TokenMetadata, // V0
+    Enumeration,   // V0
+    Approval,      // V0
+    Earnings,
+    Contents,
+    ContentTokens,
+    ContentTokenMetadata,
+    ContentEnumeration,
+    ContentApproval,
+    LicenceTokens,
+    LicenceTokenMetadata,
+    LicenceApproval,
+    LicenceMetadata,
+    CreatorKeys,
+    Roles,
+    Settlements,
+    TokenDecimals,
+    LicencesPerOwner,
+    LicenceSet { accountHash: CryptoHash },
 }
 
 #[near_bindgen]
@@ -180,12 +509,6 @@ impl Contract {
     pub fn get_contract_cnt(&self) -> u32 {
         self.cnt
     }
-    pub fn inc_cnt(&mut self) {
-        self.cnt += 1;
-    }
-    pub fn add_cnt(&mut self, cnt: u32) {
-        self.cnt += cnt;
-    }
 
     //.rem  original nft placeholder stuff modified for 2x nfts
 
@@ -203,18 +526,59 @@ impl Contract {
                 reference: None,
                 reference_hash: None,
             },
-            NFTContractMetadata {
-                spec: NFT_METADATA_SPEC.to_string(),
-                name: format!("CLI CMG licence NFT {}", env::current_account_id()),
-                symbol: "CLI".to_string(),
-                icon: Some(DATA_IMAGE_SVG_NEAR_ICON.to_string()),
-                base_uri: None,
-                reference: None,
-                reference_hash: None,
-            },
+            Self::default_licence_metadata(),
         )
     } // calls -> new
 
+    fn default_licence_metadata() -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: format!("CLI CMG licence NFT {}", env::current_account_id()),
+            symbol: "CLI".to_string(),
+            icon: Some(DATA_IMAGE_SVG_NEAR_ICON.to_string()),
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    fn new_content_collection(owner_id: AccountId) -> NonFungibleToken {
+        NonFungibleToken::new(
+            StorageKey::ContentTokens,
+            owner_id,
+            Some(StorageKey::ContentTokenMetadata),
+            Some(StorageKey::ContentEnumeration),
+            Some(StorageKey::ContentApproval),
+        )
+    }
+
+    //.rem  no enumeration: near-contract-standards keys the per-owner token sets by the owner
+    //.rem  hash only, so two enumerated collections in one contract would share them
+    fn new_licence_collection(owner_id: AccountId) -> NonFungibleToken {
+        NonFungibleToken::new(
+            StorageKey::LicenceTokens,
+            owner_id,
+            Some(StorageKey::LicenceTokenMetadata),
+            None::<StorageKey>,
+            Some(StorageKey::LicenceApproval),
+        )
+    }
+
+    fn new_token_decimals() -> LookupMap<String, u8> {
+        let mut tokenDecimals = LookupMap::new(StorageKey::TokenDecimals);
+        for accepted in Config::default().acceptedTokens {
+            tokenDecimals.insert(&accepted.tokenId.to_string(), &accepted.decimals);
+        }
+        tokenDecimals
+    }
+
+    //.rem  the decimals of an FT contract that was accepted at some point
+    fn token_decimals(&self, token: &str) -> u8 {
+        self.tokenDecimals
+            .get(&token.to_string())
+            .unwrap_or_else(|| env::panic_str(&format!("{} was never accepted", token)))
+    }
+
     #[init]
     pub fn new(
         owner_id: AccountId,
@@ -230,218 +594,487 @@ impl Contract {
 
         assert!(!env::state_exists(), "Already initialized");
         metadata.assert_valid();
+        licenceMetadata.assert_valid();
+        migration::write_state_version();
         Self {
-            tokens: NonFungibleToken::new(
-                StorageKey::NonFungibleToken,
-                owner_id.clone(),
-                Some(StorageKey::TokenMetadata),
-                Some(StorageKey::Enumeration),
-                Some(StorageKey::Approval),
-            ),
-            licenceTokens: NonFungibleToken::new(
-                StorageKey::NonFungibleToken,
-                owner_id,
-                Some(StorageKey::TokenMetadata),
-                Some(StorageKey::Enumeration),
-                Some(StorageKey::Approval),
-            ),
+            tokens: Self::new_content_collection(owner_id.clone()),
+            licenceTokens: Self::new_licence_collection(owner_id),
             lastTokenId: 1_000,
-            lastContentTokenId: 1_000_000,
+            lastContentTokenId: LICENCE_TOKEN_ID_BASE,
             metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
-            licenceMetadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
+            licenceMetadata: LazyOption::new(StorageKey::LicenceMetadata, Some(&licenceMetadata)),
             cnt: 0,
-            contents: HashMap::new(),
-            emptyContentRec: ContentRec::default(),
-            lock: 0,
-        }
-    }
-
-    //.pub  dash accessors
-
-    pub fn dash_get_contents(&self) -> Vec<&ContentRec> {
-        let mut ret: Vec<&ContentRec> = Default::default();
-        for (contentKeyKey, contentRec) in &self.contents {
-            ret.push(contentRec);
+            contents: UnorderedMap::new(StorageKey::Contents),
+            earnings: LookupMap::new(StorageKey::Earnings),
+            config: Config::default(),
+            creatorKeys: LookupMap::new(StorageKey::CreatorKeys),
+            roles: UnorderedMap::new(StorageKey::Roles),
+            paused: Vec::new(),
+            settlements: UnorderedMap::new(StorageKey::Settlements),
+            tokenDecimals: Self::new_token_decimals(),
+            licencesPerOwner: LookupMap::new(StorageKey::LicencesPerOwner),
         }
-        ret
     }
 
-    //.dev  debug_______________________________
-
-    pub fn spectrum() {
-        log!("{FgRed}: FgRed ======= testX{}", R);
-        log!("{FgGreen}: FgGreen ======= testX{}", R);
-        log!("{FgBrightGreen}: FgBrightGreen ======= testX{}", R);
-        log!("{FgYellow}: FgYellow ======= testX{}", R);
-        log!("{H}: H ======= testX{}", R);
-        log!("{FgBlue}: FgBlue ======= testX{}", R);
-        log!("{FgLime}: FgLime ======= testX{}", R);
-        log!("{FgCyan}: FgCyan ======= testX{}", R);
-        log!("{FgWhite}: FgWhite ======= testX{}", R);
-        log!("{R}: R ======= testX{}", R);
-        log!("{FgOrange}: FgOrange ======= testX{}", R);
-        log!("{FgPurple}: FgPurple ======= testX{}", R);
-        log!("{FgA}: FgA ======= testX{}", R);
-        log!("{FgZ}: FgZ ======= testX{}", R);
-        log!("{FgT}: FgT ======= testX{}", R);
-        log!("{FgX}: FgX ======= testX{}", R);
-    }
-
-    fn print_env(&self, pre: &str) {
-        log!(
-            "{pre}env::current_account_id(): {}{R}",
-            env::current_account_id()
-        );
-        log!(
-            "{pre}env::account_balance: {} NEAR{R}",
-            (env::account_balance() as f64) / 1E24
-        );
-        log!(
-            "{pre}env::signer_account_id: {}{R}",
-            env::signer_account_id()
-        );
+    //.pub  state migration (owner only)
+    //.rem  upgrades the stored state from whatever version it has to migration::STATE_VERSION
+    //.rem  current state is kept as is, so upgrade can always chain it
+
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let version = migration::state_version();
+        //.rem  one arm per older version, each upgrading straight to the current layout
+        let this = match version {
+            0 => Self::migrate_v0(),
+            migration::STATE_VERSION => {
+                let this: Self =
+                    env::state_read().unwrap_or_else(|| env::panic_str("No state to migrate"));
+                migration::assert_migrator(&this.tokens.owner_id);
+                this
+            }
+            _ => env::panic_str(&format!("Unknown state version {}", version)),
+        };
+        migration::write_state_version();
         log!(
-            "{pre}env::predecessor_account_id: {}{R}",
-            env::predecessor_account_id()
+            "{FgOrange}migrate: state version {} -> {}{R}",
+            version,
+            migration::STATE_VERSION
         );
-        log!(
-            "{pre}env::attached_deposit: {} NEAR{R}",
-            (env::attached_deposit() as f32) / 1E24
+        this
+    }
+
+    //.pub  self-upgrade (owner only)
+    //.rem  deploys `code` to this account and calls migrate in the same receipt,
+    //.rem  if migrate fails the deployment is reverted as well
+
+    pub fn upgrade(&self, code: Base64VecU8) -> Promise {
+        self.assert_role(Role::Owner, "upgrade");
+        let migrateGas = env::prepaid_gas()
+            .0
+            .checked_sub(env::used_gas().0 + UPGRADE_GAS.0)
+            .filter(|gas| *gas >= MIN_MIGRATE_GAS.0)
+            .map(Gas);
+        require!(
+            migrateGas.is_some(),
+            format!(
+                "Attach at least {} Tgas for upgrade and migrate",
+                (UPGRADE_GAS.0 + MIN_MIGRATE_GAS.0) / 1_000_000_000_000
+            )
         );
-        log!("{pre}{}{R}", ugas());
+        let migrateGas = migrateGas.unwrap();
         log!(
-            "{pre}env::prepaid_gas: {} G{R}",
-            env::prepaid_gas().0 / 1_000_000_000
+            "{FgOrange}upgrade: {} bytes, migrate with {} Tgas{R}",
+            code.0.len(),
+            migrateGas.0 / 1_000_000_000_000
         );
-        log!("{pre}env::storage_usage: {}{R}", env::storage_usage());
+        Promise::new(env::current_account_id())
+            .deploy_contract(code.into())
+            .function_call("migrate".into(), Vec::new(), 0, migrateGas)
     }
 
-    fn showContentInfo(&self, msg: &str, contentId: &str, contentRec: &ContentRec) {
-        log!("{FgX}{msg}: {}{R}", contentId);
+    //.rem  moves the inline V0 contents HashMap into the contents UnorderedMap, one record per key
+    //.rem  and starts the two NFT collections over on their own prefixes, see migrate_nfts
+    fn migrate_v0() -> Self {
+        let old: migration::ContractV0 =
+            env::state_read().unwrap_or_else(|| env::panic_str("No state to migrate"));
+        migration::assert_migrator(&old.tokens.owner_id);
+        let mut contents = UnorderedMap::new(StorageKey::Contents);
+        for (key, contentRec) in old.contents {
+            contents.insert(&key, &VersionedContentRec::V0(contentRec));
+        }
+        log!("{FgOrange}migrate: {} contents moved{R}", contents.len());
+
+        log!("{FgOrange}migrate: NFTs are moved by migrate_nfts{R}");
+
+        //.rem  V0 stored the content metadata under both keys
+        Self {
+            tokens: Self::new_content_collection(old.tokens.owner_id.clone()),
+            licenceTokens: Self::new_licence_collection(old.tokens.owner_id),
+            lastTokenId: old.lastTokenId,
+            lastContentTokenId: old.lastContentTokenId,
+            metadata: old.metadata,
+            licenceMetadata: LazyOption::new(
+                StorageKey::LicenceMetadata,
+                Some(&Self::default_licence_metadata()),
+            ),
+            cnt: old.cnt,
+            contents,
+            earnings: LookupMap::new(StorageKey::Earnings),
+            config: Config::default(),
+            creatorKeys: LookupMap::new(StorageKey::CreatorKeys),
+            roles: UnorderedMap::new(StorageKey::Roles),
+            paused: Vec::new(),
+            settlements: UnorderedMap::new(StorageKey::Settlements),
+            tokenDecimals: Self::new_token_decimals(),
+            licencesPerOwner: LookupMap::new(StorageKey::LicencesPerOwner),
+        }
     }
 
-    fn _showContentList(&self, withBidding: bool) {
-        log!("{FgYellow}Current content list:{}", R);
-        for (contentKeyKey, contentRec) in &self.contents {
-            self.showContentInfo("Content", contentKeyKey, contentRec);
-            if withBidding {
-                if contentRec.tokenId > 0 {
-                    let scoutList = self.get_nft_owners_for(contentRec.tokensArr);
+    //.rem  second step of migrate, in batches to stay within the gas limit
+    //.rem  index space: the contents first (content NFTs of `limit` contents per call),
+    //.rem  then the licence NFTs (`limit` per call); returns the next from_index, none when done
+    //.rem  safe to repeat, NFTs that are already moved are skipped
+
+    pub fn migrate_nfts(&mut self, from_index: U64, limit: u64) -> Option<U64> {
+        migration::assert_migrator(&self.tokens.owner_id);
+        require!(limit > 0, "The limit must be positive");
+        let mut shared = migration::shared_nft_collection(self.tokens.owner_id.clone());
+        let contentCount = self.contents.len();
+        let from = from_index.0;
+
+        if from < contentCount {
+            let tokenIds: Vec<TokenId> = self
+                .contents
+                .values()
+                .map(VersionedContentRec::into_current)
+                .skip(from as usize)
+                .take(limit as usize)
+                .filter(|contentRec| contentRec.tokenId > 0)
+                .flat_map(|contentRec| {
+                    (0..=contentRec.bidvalArr.len())
+                        .map(move |i| tokenId2Str(contentRec.tokenId + i))
+                })
+                .collect();
+            migration::split_nft_collection(&mut shared, &tokenIds, &mut self.tokens);
+            log!("{FgOrange}migrate_nfts: {} content NFTs{R}", tokenIds.len());
+            //.rem  the licences start on the next call, at licence index 0
+            return Some(U64(std::cmp::min(from + limit, contentCount)));
+        }
 
-                    for (i, scout) in scoutList.iter().enumerate() {
-                        log!(
-                            "{FgT}Slot[{i}]: ${} #{} for {}{R}",
-                            contentRec.bidvalArr[i],
-                            tokenId2Str(contentRec.tokensArr[i]),
-                            scout
-                        );
-                    }
-                } else {
-                    log!("No bids for {}", contentKeyKey);
-                }
+        let firstId = LICENCE_TOKEN_ID_BASE + 1 + (from - contentCount) as usize;
+        if firstId > self.lastContentTokenId {
+            return None;
+        }
+        let lastId = std::cmp::min(firstId + limit as usize - 1, self.lastContentTokenId);
+        let tokenIds: Vec<TokenId> = (firstId..=lastId).map(tokenId2Str).collect();
+        migration::split_nft_collection(&mut shared, &tokenIds, &mut self.licenceTokens);
+        for tokenId in &tokenIds {
+            if let Some(ownerId) = self.licenceTokens.owner_by_id.get(tokenId) {
+                self.index_licence(&ownerId, tokenId);
             }
         }
+        log!("{FgOrange}migrate_nfts: {} licence NFTs{R}", tokenIds.len());
+        Some(U64(from + limit))
     }
 
-    pub fn showContentList(&self) {
-        self._showContentList(false)
+    //.pub  platform config
+
+    pub fn get_config(&self) -> Config {
+        self.config.clone()
     }
 
-    pub fn showContentListWithBidding(&self) {
-        self._showContentList(true)
+    //.rem  the token the test methods bid with
+    fn default_token(&self) -> String {
+        self.config.acceptedTokens[0].tokenId.to_string()
     }
 
-    pub fn show_content_nfts(&self) {
-        let tokens = self.tokens.nft_tokens(None, Some(1000));
-        for token in tokens {
-            let meta = token.metadata.unwrap();
-            log!(
-                "{FgOrange}#{} {FgCyan}{} {R}{} {FgGreen}{}{R}",
-                token.token_id,
-                token.owner_id.to_string(),
-                meta.title.unwrap(),
-                meta.media.unwrap()
+    pub fn set_config(&mut self, config: Config) -> Config {
+        self.assert_role(Role::Owner, "change the config");
+        require!(
+            config.treasuryPt <= 100,
+            "Treasury percent must be at most 100"
+        );
+        require!(
+            config.licencePricePt > 0,
+            "Licence price percent must be positive"
+        );
+        require!(
+            !config.acceptedTokens.is_empty(),
+            "At least one token must be accepted"
+        );
+        for accepted in &config.acceptedTokens {
+            require!(
+                accepted.decimals as u32 <= YOCTO_DECIMALS,
+                format!(
+                    "{} can have at most {} decimals",
+                    accepted.tokenId, YOCTO_DECIMALS
+                )
             );
+            self.tokenDecimals
+                .insert(&accepted.tokenId.to_string(), &accepted.decimals);
         }
+        CmgEvent::ConfigChanged {
+            old: self.config.clone(),
+            new: config.clone(),
+        }
+        .emit();
+        self.config = config;
+        self.config.clone()
     }
 
-    pub fn show_licence_nfts(&self) {
-        let tokens = self.licenceTokens.nft_tokens(None, Some(1000));
-        for token in tokens {
-            let meta = token.metadata.unwrap();
-            log!(
-                "{FgOrange}#{} {FgCyan}{} {R}{} {FgGreen}{}{R}",
-                token.token_id,
-                token.owner_id.to_string(),
-                meta.title.unwrap(),
-                meta.media.unwrap()
-            );
+    //.pub  roles
+    //.rem  the owner grants and revokes admins, admins grant and revoke operators
+
+    pub fn get_role(&self, accountId: AccountId) -> Option<Role> {
+        if accountId == self.tokens.owner_id {
+            Some(Role::Owner)
+        } else {
+            self.roles.get(&accountId)
+        }
+    }
+
+    //.rem  the granted roles, the owner is not listed
+    pub fn get_roles(&self) -> Vec<(AccountId, Role)> {
+        self.roles.to_vec()
+    }
+
+    pub fn grant_role(&mut self, accountId: AccountId, role: Role) {
+        require!(role != Role::Owner, "The owner role can not be granted");
+        require!(
+            accountId != self.tokens.owner_id,
+            "The owner has every role"
+        );
+        let previous = self.roles.get(&accountId);
+        let manager = previous
+            .map_or(role, |previous| previous.max(role))
+            .manager();
+        self.assert_role(manager, &format!("grant the {:?} role", role));
+        self.roles.insert(&accountId, &role);
+        CmgEvent::RoleGranted {
+            account_id: accountId,
+            role,
+            by: env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    pub fn revoke_role(&mut self, accountId: AccountId) {
+        let role = self
+            .roles
+            .get(&accountId)
+            .unwrap_or_else(|| env::panic_str(&format!("{} has no role", accountId)));
+        self.assert_role(role.manager(), &format!("revoke the {:?} role", role));
+        self.roles.remove(&accountId);
+        CmgEvent::RoleRevoked {
+            account_id: accountId,
+            role,
+            by: env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    fn has_role(&self, accountId: &AccountId, role: Role) -> bool {
+        self.get_role(accountId.clone())
+            .is_some_and(|granted| granted >= role)
+    }
+
+    fn assert_role(&self, role: Role, action: &str) {
+        require!(
+            self.has_role(&env::predecessor_account_id(), role),
+            format!("Only {} can {}", role.holder(), action)
+        );
+    }
+
+    //.pub  emergency pause
+    //.rem  guardians pause, admins unpause; no scopes is all of them
+    //.rem  paused FT bids and licence purchases are returned to the sender by ft_on_transfer
+
+    pub fn pause(&mut self, scopes: Option<Vec<PauseScope>>) -> Vec<PauseScope> {
+        self.assert_role(Role::Guardian, "pause");
+        for scope in scopes.unwrap_or_else(|| PauseScope::ALL.to_vec()) {
+            if !self.paused.contains(&scope) {
+                self.paused.push(scope);
+            }
         }
+        self.emit_pause_changed();
+        self.paused.clone()
     }
 
-    pub fn show_nfts(&self) {
-        self.show_content_nfts();
-        self.show_licence_nfts();
+    pub fn unpause(&mut self, scopes: Option<Vec<PauseScope>>) -> Vec<PauseScope> {
+        self.assert_role(Role::Admin, "unpause");
+        let scopes = scopes.unwrap_or_else(|| PauseScope::ALL.to_vec());
+        self.paused.retain(|scope| !scopes.contains(scope));
+        self.emit_pause_changed();
+        self.paused.clone()
     }
 
-    // rem subgraph serializers
+    pub fn get_paused(&self) -> Vec<PauseScope> {
+        self.paused.clone()
+    }
+
+    fn is_paused(&self, scope: PauseScope) -> bool {
+        self.paused.contains(&scope)
+    }
+
+    fn assert_not_paused(&self, scope: PauseScope) {
+        require!(!self.is_paused(scope), format!("{:?} are paused", scope));
+    }
+
+    fn emit_pause_changed(&self) {
+        CmgEvent::PauseChanged {
+            paused: self.paused.clone(),
+            by: env::predecessor_account_id(),
+        }
+        .emit();
+    }
 
-    fn emit_content_bid(&self, contentKey: &ContentKey) {
-        let contentRef: &ContentRec = &self.contents[&contentKey.key];
-        let mut json = format!("EVENT_JSON:{{\"event\": \"content_bid\", \"data\": {{\"content_id\": \"{}\", \"bids\": [", contentKey.key);
+    //.pub  dash accessors
 
-        let aggMap: HashMap<String, (i32, f32)> = self.get_content_owners_pts_vals(contentKey);
-        let date = 1270000000;
-        let mut comma = "";
-        for (owner, ptval) in &aggMap {
-            log!("owner: {} pt: {} val: {}", owner, ptval.0, ptval.1);
-            json.push_str(&format!(
-                "{}{{\"owner\":\"{}\", \"value\": {}, \"percentage\": {}, \"date\": {} }}",
-                comma, owner, ptval.1, ptval.0, date
-            ));
-            comma = ",";
+    //.rem  paged, only the requested records are read from storage
+    //.rem  with a status only the matching records are paged, so every record up to the end of
+    //.rem  the page is read and deserialized: the gas grows with the number of contents, not the
+    //.rem  limit, and a large contract runs into the 200 Tgas view limit. Past that, page without
+    //.rem  a status and filter on the client
+    pub fn dash_get_contents(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<u64>,
+        status: Option<ContentStatus>,
+    ) -> Vec<ContentRec> {
+        let skip = from_index.map_or(0, |i| i.0 as usize);
+        let limit = limit.unwrap_or(DASH_PAGE_SIZE) as usize;
+        match status {
+            None => self
+                .contents
+                .values()
+                .skip(skip)
+                .map(VersionedContentRec::into_current)
+                .take(limit)
+                .collect(),
+            Some(status) => self
+                .contents
+                .values()
+                .map(VersionedContentRec::into_current)
+                .filter(|content| content.status == status)
+                .skip(skip)
+                .take(limit)
+                .collect(),
         }
-        json.push_str("]}}");
-        log!("{}", json);
     }
 
-    fn emit_content_licensing(&self, contentKey: &ContentKey, scoutId: &str, val: f32) {
-        let contentRef: &ContentRec = &self.contents[&contentKey.key];
-        log!("EVENT_JSON:{{\"event\": \"content_licensing\", \"data\": {{\"content_id\": \"{}\", \"licence\": {{\"buyer\": \"{}\", \"price\": {} }} }} }}", contentKey.key, scoutId, val);
+    pub fn dash_get_contents_count(&self) -> u64 {
+        self.contents.len()
+    }
+
+    // rem subgraph events
+
+    fn emit_content_bid(&self, contentKey: &ContentKey, token: &str) {
+        let contentRef = self.get_content_by_key(contentKey);
+        let aggMap: HashMap<String, (i32, Amount)> = self.get_content_owners_pts_vals(contentKey);
+        let mut bids: Vec<BidShare> = aggMap
+            .into_iter()
+            .map(|(owner, (percentage, value))| {
+                log!("owner: {} pt: {} val: {}", owner, percentage, value);
+                BidShare {
+                    owner,
+                    value,
+                    percentage,
+                }
+            })
+            .collect();
+        bids.sort_by(|a, b| a.owner.cmp(&b.owner)); // HashMap order is not stable
+        CmgEvent::ContentBid {
+            content_id: contentKey.key.clone(),
+            token: token.to_string(),
+            slots: contentRef.tokensArr.len(),
+            slot_pt: contentRef.slotPt,
+            creator_pt: contentRef.creatorPt,
+            bids,
+        }
+        .emit();
     }
 
-    fn emit_transfer_funds(&self, msg: &str, from: &str, to: &str, val: f32) {
-        // let fmt = String::from(r#"EVENT_JSON:{{"event": "transfer_funds", "data": {{"from": "{}", "to": "{}" "value": "{}"}}"#);
-        // log!(format!(fmt, from, to, val));
-        //.rem  Rust is hell
-        log!("EVENT_JSON:{{\"event\": \"transfer_funds\", \"data\": {{\"from\": \"{}\", \"to\": \"{}\" \"value\": \"{}\"}} }}", env::signer_account_id(), to, val);
-        //.fix: no from param needed, it's always the signer
+    fn emit_payout(
+        contentKey: &ContentKey,
+        accountId: &str,
+        token: &str,
+        amount: Amount,
+        reason: PayoutReason,
+    ) {
+        if amount.is_zero() {
+            return;
+        }
+        CmgEvent::Payout {
+            content_id: contentKey.key.clone(),
+            account_id: accountId.to_string(),
+            token: token.to_string(),
+            amount,
+            reason,
+        }
+        .emit();
     }
 
     // int basic content ops
 
-    fn create_content_key(contentId: &str, creatorId: &str, timestamp: u64) -> ContentKey {
+    fn content_key_string(contentId: &str, creatorId: &str, timestamp: u64) -> String {
+        format!("{contentId}:{creatorId}:{timestamp}")
+    }
+
+    fn create_content_key(contentId: &str, creatorId: &AccountId, timestamp: u64) -> ContentKey {
         ContentKey {
-            key: format!("{contentId}:{creatorId}:{timestamp}"),
+            key: Self::content_key_string(contentId, creatorId.as_str(), timestamp),
             contentId: String::from(contentId),
-            creatorId: String::from(creatorId),
+            creatorId: creatorId.clone(),
             timestamp,
         }
     }
 
-    fn create_new_content(&mut self, contentKey: &ContentKey) {
-        let content: ContentRec = ContentRec {
-            creatorId: contentKey.creatorId.clone(),
+    //.rem  for creator ids from arguments and messages, nothing is registered under an invalid one
+    fn parse_content_key(contentId: &str, creatorId: &str, timestamp: u64) -> ContentKey {
+        let accountId = creatorId.parse::<AccountId>();
+        require!(
+            accountId.is_ok(),
+            format!(
+                "Content {} is not registered",
+                Self::content_key_string(contentId, creatorId, timestamp)
+            )
+        );
+        Self::create_content_key(contentId, &accountId.unwrap(), timestamp)
+    }
+
+    fn assert_slot_layout(slotCount: usize, slotPt: u32) {
+        require!(
+            (1..=MAX_SLOT_COUNT).contains(&slotCount),
+            format!("Slot count must be between 1 and {}", MAX_SLOT_COUNT)
+        );
+        require!(
+            slotPt > 0 && (slotCount as u32) * slotPt < 100,
+            format!(
+                "{} slots of {}% leave nothing for the creator",
+                slotCount, slotPt
+            )
+        );
+    }
+
+    fn new_content_rec(contentKey: &ContentKey, slotCount: usize, slotPt: u32) -> ContentRec {
+        Self::assert_slot_layout(slotCount, slotPt);
+        ContentRec {
+            creatorId: contentKey.creatorId.to_string(),
             contentId: contentKey.contentId.clone(),
             timestamp: contentKey.timestamp,
+            slotPt,
+            creatorPt: 100 - slotCount as u32 * slotPt,
+            bidvalArr: vec![Amount::ZERO; slotCount],
+            minIncrement: DEFAULT_MIN_INCREMENT,
+            minIncrementPt: DEFAULT_MIN_INCREMENT_PT,
             ..ContentRec::default()
+        }
+    }
+
+    fn create_new_content(
+        &mut self,
+        contentKey: &ContentKey,
+        slotCount: usize,
+        slotPt: u32,
+        metadata: ContentMetadata,
+    ) {
+        metadata.assert_valid();
+        let content = ContentRec {
+            metadata,
+            ..Self::new_content_rec(contentKey, slotCount, slotPt)
         };
-        self.contents.insert(contentKey.key.clone(), content);
+        self.save_content(contentKey, &content);
         self.create_content_nfts(contentKey);
     }
 
-    fn get_content_or_none(&self, contentKey: &ContentKey) -> Option<&ContentRec> {
-        match self.contents.get(&contentKey.key) {
+    fn load_content(&self, key: &str) -> Option<ContentRec> {
+        self.contents
+            .get(&key.to_string())
+            .map(VersionedContentRec::into_current)
+    }
+
+    fn get_content_or_none(&self, contentKey: &ContentKey) -> Option<ContentRec> {
+        match self.load_content(&contentKey.key) {
             Some(content) => Some(content),
             None => {
                 log!("{FgPurple}contentKey not found. {}{R}", contentKey.key);
@@ -462,13 +1095,51 @@ impl Contract {
         self.lastContentTokenId
     }
 
-    fn create_content_nft(&mut self, contentKey: &ContentKey, tokenId: usize, pt: usize) {
-        let receiverId = AccountId::new_unchecked(contentKey.creatorId.to_string());
+    //.rem  NFT metadata from the creator's content metadata, the placeholder image without media
+    fn nft_metadata(
+        contentKey: &ContentKey,
+        metadata: &ContentMetadata,
+        tokenId: usize,
+        title: String,
+    ) -> TokenMetadata {
+        TokenMetadata {
+            title: Some(title),
+            description: metadata.description.clone(),
+            extra: Some(contentKey.key.clone()),
+            media: Some(
+                metadata
+                    .media
+                    .clone()
+                    .unwrap_or_else(|| tokenId2URL(tokenId)),
+            ),
+            media_hash: metadata.mediaHash.clone(),
+            reference: metadata.reference.clone(),
+            ..DEFAULT_TOKEN_METADATA
+        }
+    }
+
+    fn content_nft_metadata(
+        contentKey: &ContentKey,
+        metadata: &ContentMetadata,
+        tokenId: usize,
+        pt: usize,
+    ) -> TokenMetadata {
+        let name = metadata.title.as_ref().unwrap_or(&contentKey.key);
+        let title = format!("{}% of {} #{}", pt, name, tokenId2Str(tokenId));
+        Self::nft_metadata(contentKey, metadata, tokenId, title)
+    }
+
+    fn create_content_nft(
+        &mut self,
+        contentKey: &ContentKey,
+        metadata: &ContentMetadata,
+        tokenId: usize,
+        pt: usize,
+    ) {
+        let receiverId = contentKey.creatorId.clone();
         let tokenIdStr = tokenId2Str(tokenId);
-        let extra: String = contentKey.key.clone();
-        let url = tokenId2URL(tokenId);
-        let title = format!("{}% of {} #{}", pt, contentKey.key, tokenIdStr);
 
+        #[cfg(feature = "dev")]
         log!(
             "create_content_nft: balance={H}{}{R}mNEAR ugas={H}{:#?}{R}G token: #{} for {} ({}%)",
             1000 * env::account_balance() / E24,
@@ -478,26 +1149,29 @@ impl Contract {
             pt
         );
 
-        let token_metadata = Some(TokenMetadata {
-            title: Some(title),
-            description: None,
-            extra: Some(extra),
-            media: Some(url),
-            ..DEFAULT_TOKEN_METADATA
-        });
+        let token_metadata = Some(Self::content_nft_metadata(
+            contentKey, metadata, tokenId, pt,
+        ));
         //self.tokens.internal_mint(tokenIdStr, receiverId, token_metadata);
         //.rem  calling non-standard minting method:
         self.tokens
             .internal_mint_with_refund(tokenIdStr, receiverId, token_metadata, None);
     }
 
-    fn create_licence_nft(&mut self, contentKey: &ContentKey, receiverId: &AccountId, price: f32) {
+    //.rem  a licence keeps the content metadata it was bought with
+    fn create_licence_nft(
+        &mut self,
+        contentKey: &ContentKey,
+        metadata: &ContentMetadata,
+        receiverId: &AccountId,
+        price: Amount,
+    ) -> TokenId {
         let tokenId = self.get_next_licence_tokenid();
         let tokenIdStr = tokenId2Str(tokenId);
-        let extra: String = contentKey.key.clone();
-        let url = tokenId2URL(tokenId);
-        let title = format!("${} for {} #{}", price, contentKey.key, tokenIdStr);
+        let name = metadata.title.as_ref().unwrap_or(&contentKey.key);
+        let title = format!("${} for {} #{}", price, name, tokenIdStr);
 
+        #[cfg(feature = "dev")]
         log!(
             "create_licence_nft: balance={H}{}{R}mNEAR ugas={H}{:#?}{R}G token: #{} for {} (${})",
             1000 * env::account_balance() / E24,
@@ -507,48 +1181,56 @@ impl Contract {
             price
         );
 
-        let token_metadata = Some(TokenMetadata {
-            title: Some(title),
-            description: None,
-            extra: Some(extra),
-            media: Some(url),
-            ..DEFAULT_TOKEN_METADATA
-        });
+        let token_metadata = Some(Self::nft_metadata(contentKey, metadata, tokenId, title));
         //self.tokens.internal_mint(tokenIdStr, receiverId, token_metadata);
         //.rem  calling non-standard minting method:
-        self.licenceTokens.internal_mint_with_refund(
-            tokenIdStr,
-            receiverId.clone(),
-            token_metadata,
-            None,
-        );
+        let tokenId = self
+            .licenceTokens
+            .internal_mint_with_refund(tokenIdStr, receiverId.clone(), token_metadata, None)
+            .token_id;
+        self.index_licence(receiverId, &tokenId);
+        tokenId
+    }
+
+    //.rem  licences are never transferred, so an owner's set only grows
+    fn index_licence(&mut self, ownerId: &AccountId, tokenId: &TokenId) {
+        let mut licences = self.licencesPerOwner.get(ownerId).unwrap_or_else(|| {
+            UnorderedSet::new(StorageKey::LicenceSet {
+                accountHash: env::sha256_array(ownerId.as_bytes()),
+            })
+        });
+        licences.insert(tokenId);
+        self.licencesPerOwner.insert(ownerId, &licences);
     }
 
-    // rem create 1 + BIDPT content nfts
+    // rem create 1 + slot count content nfts
 
     fn create_content_nfts(&mut self, contentKey: &ContentKey) -> usize {
         // no need to return tokenId
+        let mut contentRec = self.get_content_by_key(contentKey);
+        let (slotCount, slotPt, creatorPt) = (
+            contentRec.bidvalArr.len(),
+            contentRec.slotPt,
+            contentRec.creatorPt,
+        );
         let tokenIdRef = self.get_next_tokenid();
-        self.create_content_nft(contentKey, tokenIdRef, 100 - BIDPT);
+        let metadata = contentRec.metadata.clone();
+        self.create_content_nft(contentKey, &metadata, tokenIdRef, creatorPt as usize);
 
-        for i in 1..(BIDPT + 1) {
-            self.create_content_nft(contentKey, tokenIdRef + i, 1);
+        for i in 1..(slotCount + 1) {
+            self.create_content_nft(contentKey, &metadata, tokenIdRef + i, slotPt as usize);
         }
-        let mut contentRec: &mut ContentRec = self.get_content_by_key_unguarded(contentKey);
         contentRec.tokenId = tokenIdRef;
-        let mut ret = [0; BIDPT];
-        for i in 0..BIDPT {
-            ret[i] = tokenIdRef + i + 1;
-        }
-        contentRec.tokensArr = ret;
+        contentRec.tokensArr = (1..(slotCount + 1)).map(|i| tokenIdRef + i).collect();
+        self.save_content(contentKey, &contentRec);
 
         tokenIdRef
     }
 
     // rem create licence nft
 
-    pub fn get_nft_owners_for(&self, tokensArr: [usize; BIDPT]) -> [String; BIDPT] {
-        let mut ret: [String; BIDPT] = Default::default();
+    pub fn get_nft_owners_for(&self, tokensArr: Vec<usize>) -> Vec<String> {
+        let mut ret: Vec<String> = vec![String::new(); tokensArr.len()];
 
         for (i, &tokenId) in tokensArr.iter().enumerate() {
             let token_id = tokenId2Str(tokenId);
@@ -571,64 +1253,20 @@ impl Contract {
         ret
     }
 
-    pub fn modcont(&mut self) {
-        //.rem: test only
-        let mut keys: Vec<String> = Vec::new();
-        for (contentKeyKey, contentRec) in &self.contents {
-            keys.push(String::from(contentKeyKey));
-        }
-        for key in keys {
-            //let mut contentRec = self.contents.get_mut(&key).unwrap();
-            let mut contentRec = self.get_content_by_keykey_unguarded(&key);
-            contentRec.tokenId = 2000;
-        }
-    }
-
-    fn confirm_content_by_key(&mut self, contentKey: &ContentKey) -> bool {
-        if let Some(content) = self.get_content_or_none(contentKey) {
-            log!(
-                "{FgLime}confirm_content: INFO contentKey found on 1st try! {}{R}",
-                contentKey.key
-            );
-            return true;
-        }
-
-        log!(
-            "{FgA}confirm_content: WARNING contentKey not found! Creating... {}{R}",
-            contentKey.key
+    //.rem  records are copies, changes have to be written back with save_content
+    fn save_content(&mut self, contentKey: &ContentKey, contentRec: &ContentRec) {
+        self.contents.insert(
+            &contentKey.key,
+            &VersionedContentRec::V1(contentRec.clone()),
         );
-        self.create_new_content(contentKey);
-        if let Some(content2) = self.get_content_or_none(contentKey) {
-            log!(
-                "{FgLime}confirm_content: INFO contentKey found on 2nd try! {}{R}",
-                contentKey.key
-            );
-        } else {
-            log!(
-                "{FgRed}confirm_content: ERROR contentKey not found after 2nd try! {}{R}",
-                contentKey.key
-            );
-        }
-
-        false
     }
 
-    fn get_content_by_keykey_unguarded(&mut self, key: &str) -> &mut ContentRec {
-        self.contents.get_mut(key).unwrap()
-    }
-
-    // fix contents[str]
-
-    fn get_content_by_key_unguarded(&mut self, contentKey: &ContentKey) -> &mut ContentRec {
-        self.contents.get_mut(&contentKey.key).unwrap()
-    }
-
-    fn get_content_by_key(&self, contentKey: &ContentKey) -> &ContentRec {
+    //.rem  bids and licences only on registered content, see register_content
+    fn get_content_by_key(&self, contentKey: &ContentKey) -> ContentRec {
         let someContent = self.get_content_or_none(contentKey);
-        assert!(
+        require!(
             someContent.is_some(),
-            "get_content ERROR: not found {}",
-            contentKey.key
+            format!("Content {} is not registered", contentKey.key)
         );
         someContent.unwrap()
     }
@@ -638,17 +1276,353 @@ impl Contract {
         contentId: String,
         timestamp: u64,
         creatorId: String,
-    ) -> &ContentRec {
-        let contentKey = Self::create_content_key(&contentId, &creatorId, timestamp);
-        let contentRef: Option<&ContentRec> = self.contents.get(&contentKey.key);
-
-        contentRef.unwrap_or(&self.emptyContentRec)
+    ) -> ContentRec {
+        let key = Self::content_key_string(&contentId, &creatorId, timestamp);
+        //.rem  delisted content is shown like unregistered content
+        self.load_content(&key)
+            .filter(|content| content.status != ContentStatus::Delisted)
+            .unwrap_or_default()
+    }
+
+    //.pub  content registration
+    //.rem  lets the creator pick the slot layout, e.g. 10 slots of 2% or 50 slots of 1%
+    //.rem  content has to be registered before it can be bid on or licensed
+
+    #[payable]
+    pub fn register_content(
+        &mut self,
+        contentId: String,
+        timestamp: u64,
+        slotCount: usize,
+        slotPt: u32,
+        metadata: Option<ContentMetadata>,
+    ) -> ContentRec {
+        let creatorId = env::predecessor_account_id();
+        self.register_content_internal(
+            &contentId,
+            &creatorId,
+            timestamp,
+            slotCount,
+            slotPt,
+            metadata.unwrap_or_default(),
+        )
+    }
+
+    //.rem  by an allow-listed platform, with the creator's ed25519 signature of the
+    //.rem  ContentApproval (see message.rs), made with the key set by set_creator_key
+
+    #[payable]
+    pub fn register_content_for(
+        &mut self,
+        approval: ContentApproval,
+        signature: Base64VecU8,
+    ) -> ContentRec {
+        require!(
+            self.config
+                .platformIds
+                .contains(&env::predecessor_account_id()),
+            "Only allow-listed platforms can register content for a creator"
+        );
+        require!(
+            approval.contractId == env::current_account_id(),
+            "Approval is for another contract"
+        );
+        let publicKey = self
+            .creatorKeys
+            .get(&approval.creatorId)
+            .unwrap_or_else(|| {
+                env::panic_str(&format!("{} has no creator key", approval.creatorId))
+            });
+        require!(
+            verify_ed25519(&publicKey, &approval.to_signed_bytes(), &signature.0),
+            "Invalid creator signature"
+        );
+        self.register_content_internal(
+            &approval.contentId,
+            &approval.creatorId,
+            approval.timestamp,
+            approval.slotCount,
+            approval.slotPt,
+            approval.metadata.unwrap_or_default(),
+        )
+    }
+
+    //.rem  the caller pays for the storage of the record and its NFTs, the rest is refunded
+    fn register_content_internal(
+        &mut self,
+        contentId: &str,
+        creatorId: &AccountId,
+        timestamp: u64,
+        slotCount: usize,
+        slotPt: u32,
+        metadata: ContentMetadata,
+    ) -> ContentRec {
+        let initialStorage = env::storage_usage();
+        let contentKey = Self::create_content_key(contentId, creatorId, timestamp);
+        require!(
+            self.load_content(&contentKey.key).is_none(),
+            format!("Content already registered {}", contentKey.key)
+        );
+        self.create_new_content(&contentKey, slotCount, slotPt, metadata);
+        refund_deposit(env::storage_usage() - initialStorage);
+        self.get_content_by_key(&contentKey)
+    }
+
+    //.pub  content metadata (creator only)
+    //.rem  regenerates the metadata of the content NFTs, licences keep what they were bought with
+    //.rem  longer metadata has to be paid for like at registration, shorter refunds the deposit
+
+    #[payable]
+    pub fn set_content_metadata(
+        &mut self,
+        contentId: String,
+        timestamp: u64,
+        metadata: ContentMetadata,
+    ) -> ContentRec {
+        let initialStorage = env::storage_usage();
+        let creatorId = env::predecessor_account_id();
+        let contentKey = Self::create_content_key(&contentId, &creatorId, timestamp);
+        let mut contentRec = self.get_content_by_key(&contentKey);
+        metadata.assert_valid();
+        contentRec.metadata = metadata;
+        self.save_content(&contentKey, &contentRec);
+
+        let tokens = std::iter::once((contentRec.tokenId, contentRec.creatorPt)).chain(
+            contentRec
+                .tokensArr
+                .iter()
+                .map(|&id| (id, contentRec.slotPt)),
+        );
+        if let Some(byId) = self.tokens.token_metadata_by_id.as_mut() {
+            for (tokenId, pt) in tokens {
+                let tokenMetadata = Self::content_nft_metadata(
+                    &contentKey,
+                    &contentRec.metadata,
+                    tokenId,
+                    pt as usize,
+                );
+                byId.insert(&tokenId2Str(tokenId), &tokenMetadata);
+            }
+        }
+        refund_deposit(env::storage_usage().saturating_sub(initialStorage));
+        contentRec
+    }
+
+    //.rem  the ed25519 key platforms' content approvals are checked with, none removes it
+    pub fn set_creator_key(&mut self, publicKey: Option<PublicKey>) {
+        let creatorId = env::predecessor_account_id();
+        match publicKey {
+            Some(publicKey) => {
+                require!(
+                    publicKey.curve_type() == CurveType::ED25519,
+                    "Creator key must be ed25519"
+                );
+                self.creatorKeys.insert(&creatorId, &publicKey);
+            }
+            None => {
+                self.creatorKeys.remove(&creatorId);
+            }
+        }
+    }
+
+    pub fn get_creator_key(&self, creatorId: AccountId) -> Option<PublicKey> {
+        self.creatorKeys.get(&creatorId)
+    }
+
+    //.pub  bidding window (creator only)
+    //.rem  extensionMinutes: anti-sniping, a bid in the last N minutes moves the end to now + N
+    //.rem  a start that has passed stays, once there are bids only the end can be extended
+
+    pub fn set_bidding_window(
+        &mut self,
+        contentId: String,
+        timestamp: u64,
+        biddingStart: Option<U64>,
+        biddingEnd: Option<U64>,
+        extensionMinutes: Option<u32>,
+    ) -> ContentRec {
+        let creatorId = env::predecessor_account_id().to_string();
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
+        let mut contentRec = self.get_content_by_key(&contentKey);
+        require!(!contentRec.settled, "Bidding is already settled");
+        if let (Some(start), Some(end)) = (biddingStart, biddingEnd) {
+            require!(start.0 < end.0, "Bidding end must be after its start");
+        }
+        if let Some(end) = biddingEnd {
+            require!(
+                end.0 > env::block_timestamp(),
+                "Bidding end must be in the future"
+            );
+        }
+        if let Some(start) = contentRec.biddingStart {
+            require!(
+                start.0 > env::block_timestamp() || biddingStart == Some(start),
+                "Bidding has started, its start can not be changed"
+            );
+        }
+        //.rem  without one, a window that is already set keeps its extension
+        let hasWindow = contentRec.biddingStart.is_some() || contentRec.biddingEnd.is_some();
+        let extensionMinutes = extensionMinutes.unwrap_or(if hasWindow {
+            contentRec.snipeMinutes
+        } else {
+            DEFAULT_SNIPE_MINUTES
+        });
+        if contentRec.bidvalArr.iter().any(|value| !value.is_zero()) {
+            let extended = match (contentRec.biddingEnd, biddingEnd) {
+                (Some(end), Some(newEnd)) => newEnd.0 >= end.0,
+                (None, None) => true,
+                _ => false,
+            };
+            require!(
+                biddingStart == contentRec.biddingStart
+                    && extended
+                    && extensionMinutes == contentRec.snipeMinutes,
+                "There are bids already, the bidding end can only be extended"
+            );
+        }
+
+        contentRec.biddingStart = biddingStart;
+        contentRec.biddingEnd = biddingEnd;
+        contentRec.snipeMinutes = extensionMinutes;
+        self.save_content(&contentKey, &contentRec);
+        contentRec
+    }
+
+    //.pub  minimum outbid increment (creator only)
+    //.rem  absolute and/or percent of the slot's current value, the larger one applies
+    //.rem  the absolute one has to be positive, a bid at the current value never takes a slot
+
+    pub fn set_min_increment(
+        &mut self,
+        contentId: String,
+        timestamp: u64,
+        minIncrement: Amount,
+        minIncrementPt: u32,
+    ) -> ContentRec {
+        let creatorId = env::predecessor_account_id().to_string();
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
+        let mut contentRec = self.get_content_by_key(&contentKey);
+        require!(!contentRec.settled, "Bidding is already settled");
+        require!(
+            minIncrementPt <= 100,
+            "Increment percent must be at most 100"
+        );
+        require!(
+            !minIncrement.is_zero(),
+            "The minimum increment must be positive"
+        );
+
+        contentRec.minIncrement = minIncrement;
+        contentRec.minIncrementPt = minIncrementPt;
+        self.save_content(&contentKey, &contentRec);
+        contentRec
+    }
+
+    //.rem  anyone can settle once the window is over, the result is final
+    pub fn settle_content(&mut self, contentId: String, creatorId: String, timestamp: u64) {
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
+        let mut contentRec = self.get_content_by_key(&contentKey);
+        require!(!contentRec.settled, "Bidding is already settled");
+        let end = contentRec
+            .biddingEnd
+            .unwrap_or_else(|| env::panic_str("Content has no bidding window"));
+        require!(
+            env::block_timestamp() >= end.0,
+            format!("Bidding is open until {}", end.0)
+        );
+
+        contentRec.settled = true;
+        let previous = contentRec.status;
+        if previous == ContentStatus::Open {
+            contentRec.status = ContentStatus::Closed;
+        }
+        self.save_content(&contentKey, &contentRec);
+        CmgEvent::ContentSettled {
+            content_id: contentKey.key.clone(),
+            bidding_end: end,
+        }
+        .emit();
+        if previous != contentRec.status {
+            Self::emit_content_status(&contentKey, previous, contentRec.status);
+        }
+    }
+
+    //.pub  content lifecycle (creator or admin)
+    //.rem  Draft <-> Open while there are no bids, Open <-> Closed, anything but Archived can be
+    //.rem  Delisted, Closed and Delisted can be Archived. Settled content can not be reopened.
+
+    pub fn set_content_status(
+        &mut self,
+        contentId: String,
+        creatorId: String,
+        timestamp: u64,
+        status: ContentStatus,
+    ) -> ContentRec {
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
+        let mut contentRec = self.get_content_by_key(&contentKey);
+        let caller = env::predecessor_account_id();
+        let isAdmin = self.has_role(&caller, Role::Admin);
+        require!(
+            isAdmin || caller.as_str() == contentRec.creatorId,
+            "Only the creator or an admin can change the content status"
+        );
+        let previous = contentRec.status;
+        require!(
+            previous.can_become(status),
+            format!("Content can not go from {:?} to {:?}", previous, status)
+        );
+        require!(
+            isAdmin || previous != ContentStatus::Delisted || status == ContentStatus::Archived,
+            "Only an admin can list delisted content again"
+        );
+        if status == ContentStatus::Draft {
+            require!(
+                contentRec.bidvalArr.iter().all(|val| val.is_zero()),
+                "Content with bids can not go back to Draft"
+            );
+        }
+        if status == ContentStatus::Open {
+            require!(!contentRec.settled, "Bidding is already settled");
+        }
+
+        contentRec.status = status;
+        self.save_content(&contentKey, &contentRec);
+        Self::emit_content_status(&contentKey, previous, status);
+        contentRec
+    }
+
+    fn emit_content_status(contentKey: &ContentKey, from: ContentStatus, to: ContentStatus) {
+        CmgEvent::ContentStatusChanged {
+            content_id: contentKey.key.clone(),
+            from,
+            to,
+            by: env::predecessor_account_id(),
+        }
+        .emit();
+    }
+
+    fn assert_bidding_open(content: &ContentRec) {
+        let now = env::block_timestamp();
+        require!(
+            content.status == ContentStatus::Open,
+            format!("Content is {:?}, not open for bids", content.status)
+        );
+        require!(!content.settled, "Bidding is settled");
+        if let Some(start) = content.biddingStart {
+            require!(now >= start.0, format!("Bidding starts at {}", start.0));
+        }
+        if let Some(end) = content.biddingEnd {
+            require!(now < end.0, format!("Bidding closed at {}", end.0));
+        }
     }
 
     //.pub  COTO transfer listener
-    //.rem  possible msg formats: (closing : is optional)
+    //.rem  msg is a TransferMessage, in the legacy colon form or as versioned JSON (see message.rs)
     //.rem  - bid:creator.testnet:85d491b3-18f8-40f6-be33-b83dd749a8a4:123367777:10:22.5:
     //.rem  - buy:creator.testnet:85d491b3-18f8-40f6-be33-b83dd749a8a4:123367777:60.5:
+    //.rem  the value is optional, the transferred amount is what is bid or paid. If the value
+    //.rem  is given it has to match. The unused part of a bid is returned to the sender.
+    //.rem  slots and licences go to the sender_id of the transfer, or to the message's beneficiary
 
     pub fn ft_on_transfer(
         &mut self,
@@ -656,137 +1630,423 @@ impl Contract {
         amount: U128,
         msg: String,
     ) -> PromiseOrValue<U128> {
-        let vec: Vec<&str> = msg.split(':').collect();
+        //.rem  anyone can call this, only transfers reported by an accepted FT contract are real
+        let token = env::predecessor_account_id();
+        let accepted = self
+            .config
+            .acceptedTokens
+            .iter()
+            .find(|accepted| accepted.tokenId == token);
+        let decimals = match accepted {
+            Some(accepted) => accepted.decimals,
+            None => {
+                log!(
+                    "{FgOrange}ft_on_transfer: {} is not an accepted token, returning {} to {}{R}",
+                    token,
+                    amount.0,
+                    sender_id
+                );
+                return PromiseOrValue::Value(amount);
+            }
+        };
 
-        //.chk  len require
-        //.chk  amount vs transferred coto
+        //.rem  a panic here makes the FT contract refund the whole transfer
+        let message = TransferMessage::parse(&msg)
+            .unwrap_or_else(|e| env::panic_str(&format!("Invalid transfer message: {}", e)));
+        let transferred = Amount::from_units(amount, decimals);
+        log!("ft_on_transfer: {:?}", message);
+        //.rem  not the signer: the transfer may come through a DAO, relayer or wallet contract
+        let scoutId = message.beneficiary().unwrap_or(&sender_id).to_string();
+        let scope = match message {
+            TransferMessage::Bid { .. } => PauseScope::Bids,
+            TransferMessage::Buy { .. } => PauseScope::Licences,
+        };
+        if self.is_paused(scope) {
+            log!(
+                "{FgOrange}ft_on_transfer: {:?} are paused, returning {} to {}{R}",
+                scope,
+                amount.0,
+                sender_id
+            );
+            CmgEvent::Refund {
+                account_id: sender_id,
+                token: token.to_string(),
+                amount: transferred,
+                reason: RefundReason::Paused,
+            }
+            .emit();
+            return PromiseOrValue::Value(amount);
+        }
 
-        let op = vec[0];
-        let creatorId = String::from(vec[1]);
-        let contentId = String::from(vec[2]);
-        let timestamp = vec[3].parse::<u64>().unwrap();
-        let contentKey = Self::create_content_key(&contentId, &creatorId, timestamp);
-        log!("op: {} len: {} content: {}", op, vec.len(), contentKey.key);
+        match message {
+            TransferMessage::Bid {
+                creatorId,
+                contentId,
+                timestamp,
+                pt,
+                value,
+                ..
+            } => {
+                check_declared_value(value, transferred, decimals);
+
+                let contentKey = Self::content_key_string(&contentId, &creatorId, timestamp);
+                let unused = self.add_bid_coto(
+                    contentId,
+                    creatorId,
+                    timestamp,
+                    scoutId.clone(),
+                    transferred,
+                    pt,
+                    token.as_str(),
+                );
+                //.rem  the FT only moves whole units, the rest of the per-slot rounding is credited
+                let returned = unused.to_units(decimals);
+                let remainder = unused - Amount::from_units(returned, decimals);
+                self.credit_earnings(sender_id.as_str(), token.as_str(), remainder);
+                if !remainder.is_zero() {
+                    CmgEvent::Payout {
+                        content_id: contentKey,
+                        account_id: sender_id.to_string(),
+                        token: token.to_string(),
+                        amount: remainder,
+                        reason: PayoutReason::BidRemainder,
+                    }
+                    .emit();
+                }
+                if returned.0 > 0 {
+                    CmgEvent::Refund {
+                        account_id: sender_id,
+                        token: token.to_string(),
+                        amount: Amount::from_units(returned, decimals),
+                        reason: RefundReason::UnusedBid,
+                    }
+                    .emit();
+                }
+                #[cfg(feature = "dev")]
+                self.showContentListWithBidding();
+                PromiseOrValue::Value(returned)
+            }
+            TransferMessage::Buy {
+                creatorId,
+                contentId,
+                timestamp,
+                value,
+                ..
+            } => {
+                check_declared_value(value, transferred, decimals);
+
+                self.buy_licence_internal(
+                    contentId,
+                    creatorId,
+                    timestamp,
+                    scoutId,
+                    transferred,
+                    token.as_str(),
+                );
+                #[cfg(feature = "dev")]
+                self.showContentListWithBidding();
+                PromiseOrValue::Value(U128(0))
+            }
+        }
+    }
 
-        let transferBack: PromiseOrValue<U128> = PromiseOrValue::Value(U128(0));
+    //.pub  settlements
+    //.rem  a content NFT in an nft_transfer_call belongs to the receiver until the receiver's
+    //.rem  nft_on_transfer asks for it back, nft_resolve_transfer settles who it belongs to
 
-        if op.eq("bid") {
-            require!(vec.len() > 5, "too few parts in bid parameter string");
-            let pt = vec[4].parse::<i32>().unwrap();
-            let cotoValue = vec[5].parse::<f32>().unwrap();
+    pub fn get_pending_settlements(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<(String, Settlement)> {
+        self.settlements
+            .iter()
+            .skip(from_index.map_or(0, |i| i.0 as usize))
+            .take(limit.unwrap_or(DASH_PAGE_SIZE) as usize)
+            .collect()
+    }
 
-            log!("val: {} pt: {}", cotoValue, pt);
+    //.rem  for a callback that never ran (e.g. out of gas), the NFT owners are whatever they are now
+    //.rem  and the held refunds go to the receivers, the transfers were never reverted
+    pub fn clear_settlement(&mut self, contentKey: String) -> Settlement {
+        self.assert_role(Role::Admin, "clear settlements");
+        let settlement = self
+            .settlements
+            .remove(&contentKey)
+            .unwrap_or_else(|| env::panic_str(&format!("No settlement for {}", contentKey)));
+        for held in &settlement.held {
+            self.release_held_refund(held, &held.ownerId);
+        }
+        CmgEvent::SettlementCleared {
+            content_id: contentKey,
+            token_ids: settlement.tokenIds.clone(),
+        }
+        .emit();
+        settlement
+    }
 
-            self.add_bid_coto(
-                contentId,
-                creatorId,
-                timestamp,
-                String::from(env::signer_account_id()),
-                cotoValue,
-                pt,
-            );
-            self.showContentListWithBidding();
+    //.rem  content NFTs carry their content key in the metadata extra
+    fn content_key_of(&self, tokenId: &TokenId) -> Option<String> {
+        self.tokens
+            .token_metadata_by_id
+            .as_ref()
+            .and_then(|byId| byId.get(tokenId))
+            .and_then(|metadata| metadata.extra)
+    }
 
-            // set transferBack
-        } else if op.eq("buy") {
-            require!(vec.len() > 4, "too few parts in buy parameter string");
-            let cotoValue = vec[4].parse::<f32>().unwrap();
+    fn start_settlement(&mut self, tokenId: &TokenId) {
+        if let Some(contentKey) = self.content_key_of(tokenId) {
+            let mut settlement = self.settlements.get(&contentKey).unwrap_or(Settlement {
+                tokenIds: Vec::new(),
+                since: U64(env::block_timestamp()),
+                held: Vec::new(),
+            });
+            settlement.tokenIds.push(tokenId.clone());
+            self.settlements.insert(&contentKey, &settlement);
+            CmgEvent::SettlementStarted {
+                content_id: contentKey,
+                token_ids: vec![tokenId.clone()],
+            }
+            .emit();
+        }
+    }
 
-            log!("val: {}", cotoValue);
+    //.rem  a held refund goes to the previous owner if the receiver asked for the NFT back
+    //.rem  a receiver can pass the NFT on in its nft_on_transfer, the inner transfer resolves
+    //.rem  first: one entry per transfer, and a refund returned to a previous owner that is
+    //.rem  itself a receiver in flight stays held for the outer transfer
+    fn end_settlement(&mut self, tokenId: &TokenId, previousOwnerId: &AccountId, returned: bool) {
+        if let Some(contentKey) = self.content_key_of(tokenId) {
+            if let Some(mut settlement) = self.settlements.get(&contentKey) {
+                if let Some(ix) = settlement.tokenIds.iter().position(|id| id == tokenId) {
+                    settlement.tokenIds.remove(ix);
+                }
+                let outerInFlight = settlement.tokenIds.contains(tokenId);
+                let (released, mut held): (Vec<HeldRefund>, Vec<HeldRefund>) = settlement
+                    .held
+                    .into_iter()
+                    .partition(|held| &held.tokenId == tokenId);
+                for refund in released {
+                    if !returned {
+                        self.release_held_refund(&refund, &refund.ownerId);
+                    } else if outerInFlight {
+                        held.push(HeldRefund {
+                            ownerId: previousOwnerId.clone(),
+                            ..refund
+                        });
+                    } else {
+                        self.release_held_refund(&refund, previousOwnerId);
+                    }
+                }
+                settlement.held = held;
+                if settlement.tokenIds.is_empty() {
+                    self.settlements.remove(&contentKey);
+                } else {
+                    self.settlements.insert(&contentKey, &settlement);
+                }
+                CmgEvent::SettlementEnded {
+                    content_id: contentKey,
+                    token_ids: vec![tokenId.clone()],
+                }
+                .emit();
+            }
+        }
+    }
 
-            self.buy_licence(
-                contentId,
-                creatorId,
-                timestamp,
-                String::from(env::signer_account_id()),
-                cotoValue,
-            );
-            self.showContentListWithBidding();
+    //.rem  an outbid NFT in flight changes hands, but who is refunded is only known later
+    fn hold_refund(
+        &mut self,
+        tokenId: &TokenId,
+        ownerId: &AccountId,
+        token: &str,
+        amount: Amount,
+    ) -> bool {
+        let contentKey = match self.content_key_of(tokenId) {
+            Some(contentKey) => contentKey,
+            None => return false,
+        };
+        match self.settlements.get(&contentKey) {
+            Some(mut settlement) if settlement.tokenIds.contains(tokenId) => {
+                settlement.held.push(HeldRefund {
+                    tokenId: tokenId.clone(),
+                    ownerId: ownerId.clone(),
+                    token: token.to_string(),
+                    amount,
+                });
+                self.settlements.insert(&contentKey, &settlement);
+                true
+            }
+            _ => false,
         }
+    }
 
-        transferBack
+    fn release_held_refund(&mut self, held: &HeldRefund, to: &AccountId) {
+        self.credit_earnings(to.as_str(), &held.token, held.amount);
+        CmgEvent::Refund {
+            account_id: to.clone(),
+            token: held.token.clone(),
+            amount: held.amount,
+            reason: RefundReason::OutbidInTransfer,
+        }
+        .emit();
     }
 
-    #[payable]
-    pub fn test_bid2(&mut self, ix: usize, value: f32, pt: i32) {
-        let content = testContentData[ix];
-        self.add_bid_coto(
-            String::from(content.0),
-            String::from(content.1),
-            content.2,
-            String::from(env::signer_account_id()),
-            value,
-            pt,
+    //.rem  slot selection shared by add_bid_coto and quote_bid, no state is touched
+    //.rem  panics with the same messages a real bid would be rejected with
+
+    fn plan_bid(content: &ContentRec, cotoValue: Amount, maxPercent: i32) -> BidPlan {
+        let slotPt = content.slotPt as i32;
+        let slotCount = content.bidvalArr.len();
+
+        //.rem: maxPercent has to be a whole number of slots of this content
+        require!(
+            maxPercent > 0
+                && maxPercent % slotPt == 0
+                && (maxPercent / slotPt) as usize <= slotCount,
+            format!(
+                "Percent must be a multiple of {}% and at most {}%",
+                slotPt,
+                slotCount as i32 * slotPt
+            )
         );
-        self.showContentListWithBidding();
-    }
 
-    // id: internal lock & log stuff
+        let bidLimit = cotoValue.div_parts((maxPercent / slotPt) as u128);
+        //.rem  slots are sorted ascending, so the first one is the cheapest to take
+        let (minIncrement, minNextBid) = content.min_outbid(content.bidvalArr[0]);
+        require!(
+            bidLimit >= minNextBid,
+            format!(
+                "Bid of {} per slot is too low, the cheapest slot is at {} and needs at least {} (min increment {})",
+                bidLimit, content.bidvalArr[0], minNextBid, minIncrement
+            )
+        );
 
-    fn lock_start(&mut self, pre: &str) -> bool {
-        //.rem: start locking mechanism (the caller will fail)
-        // assert!(self.lock == 0, format!("{FgRed} contract is already locked (in TX) ({}){R}", self.lock));
-        if self.lock > 0 {
-            log!(
-                "{pre} {FgRed} add_bid is already running ({}), will abort.{R}",
-                self.lock
-            );
-            false
-        } else {
-            self.lock += 1; //.rem: MUTEX start
-            log!("{pre}{FgRed} LOCKED ({}).{R}", self.lock);
-            true
+        let wonSlots: Vec<usize> = (0..slotCount)
+            .filter(|bix| bidLimit >= content.min_outbid(content.bidvalArr[*bix]).1)
+            .take((maxPercent / slotPt) as usize)
+            .collect();
+        let usedUpValue = bidLimit.times(wonSlots.len() as u128);
+
+        BidPlan {
+            bidLimit,
+            wonSlots,
+            usedUpValue,
+            minIncrement,
+            minNextBid,
         }
     }
 
-    fn lock_end(&mut self, pre: &str) {
-        //.rem: end locking mechanism
-        self.lock -= 1; //.rem: MUTEX end
-        log!("{pre}{FgRed} UNLOCKED ({}).{R}", self.lock);
+    //.pub  bid quote
+    //.rem  what a bid:<creator>:<content>:<timestamp>:<pt>:<value> transfer would do right now
+
+    pub fn quote_bid(
+        &self,
+        contentId: String,
+        creatorId: String,
+        timestamp: u64,
+        value: Amount,
+        pt: i32,
+    ) -> BidQuote {
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
+        let content = self.get_content_by_key(&contentKey);
+        Self::assert_bidding_open(&content);
+
+        let plan = Self::plan_bid(&content, value, pt);
+        let slots: Vec<SlotQuote> = plan
+            .wonSlots
+            .iter()
+            .map(|bix| {
+                let previousValue = content.bidvalArr[*bix];
+                let tokenId = content.tokensArr.get(*bix).copied();
+                let previousOwner = match tokenId {
+                    Some(tokenId) if !previousValue.is_zero() => self
+                        .tokens
+                        .owner_by_id
+                        .get(&tokenId2Str(tokenId))
+                        .map(|owner| owner.to_string()),
+                    _ => None,
+                };
+                let (creatorShare, treasuryShare) =
+                    split_creator_share(plan.bidLimit - previousValue, self.config.treasuryPt);
+                SlotQuote {
+                    slot: *bix,
+                    tokenId,
+                    previousValue,
+                    previousOwner,
+                    price: plan.bidLimit,
+                    creatorShare,
+                    treasuryShare,
+                }
+            })
+            .collect();
+
+        BidQuote {
+            percentWon: slots.len() as u32 * content.slotPt,
+            pricePerSlot: plan.bidLimit,
+            used: plan.usedUpValue,
+            refund: value - plan.usedUpValue,
+            creatorShare: slots.iter().map(|slot| slot.creatorShare).sum(),
+            treasuryShare: slots.iter().map(|slot| slot.treasuryShare).sum(),
+            minIncrement: plan.minIncrement,
+            minNextBid: plan.minNextBid,
+            slots,
+        }
     }
 
     //#f04: add_bid event handler core
     //.rem: on_ft_transfer event listener interface: add_bid_coto
+    //.rem  returns the unused part of cotoValue, the caller gives it back to the scout
 
+    #[allow(clippy::too_many_arguments)]
     fn add_bid_coto(
         &mut self,
         contentId: String,
         creatorId: String,
         timestamp: u64,
         scoutId: String,
-        cotoValue: f32,
+        cotoValue: Amount,
         maxPercent: i32,
-    ) {
-        let pre = format!("{} {FgOrange}add_bid:", self.lock);
-        require!(self.lock_start(&pre), "Aborted on locked call");
+        token: &str,
+    ) -> Amount {
+        self.assert_not_paused(PauseScope::Bids);
+        let pre = format!("{FgOrange}add_bid:");
+        #[cfg(feature = "dev")]
         self.print_env(&pre);
 
         //.rem: checking deposit
 
-        let deposit = env::attached_deposit();
-        let depositNEAR = (deposit as f32) / 1E24;
+        let deposit = Amount::from_yocto(env::attached_deposit());
         ////log!("{pre} scout: {} w/ attached deposit: {} NEAR{R}", &scoutId, depositNEAR);
         ////require!(depositNEAR >= value, format!("Requires attached deposit of at least {} NEAR", value));
 
         //.rem: getting the record
 
-        let contentKey = Self::create_content_key(&contentId, &creatorId, timestamp);
-        let contentExisted = self.confirm_content_by_key(&contentKey);
-        //let contentRef: &ContentRec = self.get_content_by_key_unguarded(&contentKey);
-        let contentRef: &ContentRec = &self.contents[&contentKey.key];
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
+        let mut contentRec = self.get_content_by_key(&contentKey);
+        Self::assert_bidding_open(&contentRec);
+        //.rem  slot values are only comparable in one token
+        if let Some(bidToken) = &contentRec.bidToken {
+            require!(
+                bidToken == token,
+                format!(
+                    "Bids on {} are paid in {}, not {}",
+                    contentId, bidToken, token
+                )
+            );
+        }
 
         //.rem: destructuring original content
 
-        let bidvalArrOrig = contentRef.bidvalArr;
-        let mut bidvalArrNew = bidvalArrOrig;
-        let mut tokensArr = contentRef.tokensArr;
-        let creatorId = contentRef.creatorId.clone();
-        let contentId = contentRef.contentId.clone();
+        let bidvalArrOrig = contentRec.bidvalArr.clone();
+        let mut bidvalArrNew = bidvalArrOrig.clone();
+        let mut tokensArr = contentRec.tokensArr.clone();
+        let creatorId = contentRec.creatorId.clone();
+        let contentId = contentRec.contentId.clone();
 
         //.rem: start bidding
 
-        let bidLimit = cotoValue / maxPercent as f32;
-        let mut remainingBids = maxPercent;
+        let plan = Self::plan_bid(&contentRec, cotoValue, maxPercent);
+        let bidLimit = plan.bidLimit;
 
         log!(
             "{pre} for {}: {FgX}{}% for ${}, bidLimit={}{R}",
@@ -797,49 +2057,39 @@ impl Contract {
         );
 
         let biddingScoutId: AccountId = scoutId.parse().unwrap();
-        let mut usedUpValue: f32 = 0.0;
+        let mut usedUpValue = Amount::ZERO;
 
-        for bix in 0..BIDPT {
-            if remainingBids < 1 {
-                break;
-            }
-            if bidvalArrOrig[bix] < bidLimit {
-                bidvalArrNew[bix] = bidLimit;
-                let token_id = tokenId2Str(tokensArr[bix]);
-
-                let oldValue = bidvalArrOrig[bix];
-                let creatorRefund = bidLimit - oldValue;
-                let scoutRefund = oldValue;
-
-                // retlog.push(format!("Will call transfer_mod with {token_id} {}", &biddingScoutId));
-                // log!("token_id={token_id} ->rebid");
-                self.rebid(&token_id, &biddingScoutId, scoutRefund); //.todo: check self-payment (self-outbid)
-                                                                     // log!("->pay_creator");
-                self.pay_creator(&creatorId, creatorRefund); // never zero, no need for if
-
-                remainingBids -= 1;
-                usedUpValue += bidLimit;
-                log!("{pre} {FgZ}->Percent slot won: %[{bix}] (NFT: {} -> {}) remaining: {} usedUpVal: {} gasUsed: {} {R}",
-          "scoutIdOld", scoutId, remainingBids, usedUpValue, env::used_gas().0 / 1_000_000_000);
-            }
+        for bix in plan.wonSlots {
+            bidvalArrNew[bix] = bidLimit;
+            let token_id = tokenId2Str(tokensArr[bix]);
+
+            let oldValue = bidvalArrOrig[bix];
+            let creatorRefund = bidLimit - oldValue;
+            let scoutRefund = oldValue;
+
+            self.rebid(&contentKey, &token_id, &biddingScoutId, scoutRefund, token); //.todo: check self-payment (self-outbid)
+            self.pay_creator(&contentKey, &creatorId, creatorRefund, token); // never zero, no need for if
+
+            usedUpValue += bidLimit;
+            #[cfg(feature = "dev")]
+            log!("{pre} {FgZ}->Percent slot won: %[{bix}] (NFT: {} -> {}) usedUpVal: {} gasUsed: {} {R}",
+          "scoutIdOld", scoutId, usedUpValue, env::used_gas().0 / 1_000_000_000);
         }
         //.todo  refund value - usedUpValue;
+        //.rem  includes the rounding leftover of the per-slot split, so nothing is lost
         let selfRefund = cotoValue - usedUpValue;
-        if selfRefund > 0.001 {
-            let refund = (selfRefund * 1E24) as u128;
+        if !selfRefund.is_zero() {
             log!(
-                "{FgCyan}Partial or unsuccessful bid, will refund {} NEAR ->{}{R}",
+                "{FgCyan}Partial or unsuccessful bid, {} {} goes back ->{}{R}",
                 selfRefund,
+                token,
                 scoutId
             );
-            //Promise::new(String::from(&scoutId).parse().unwrap()).transfer(refund);
-            self.transfer_funds("selfRefund", &scoutId, refund);
-            self.emit_transfer_funds("self_refund", &scoutId, &scoutId, selfRefund);
         } else {
             log!("{FgCyan}Successful bid, all funds used up.{}", R);
         }
 
-        fn sortBidding(arr1: &mut [f32; BIDPT], arr2: &mut [usize; BIDPT]) {
+        fn sortBidding(arr1: &mut [Amount], arr2: &mut [usize]) {
             for i in 0..arr1.len() {
                 for j in 0..arr1.len() - 1 - i {
                     if arr1[j] > arr1[j + 1] {
@@ -849,47 +2099,164 @@ impl Contract {
                 }
             }
         }
-        // for i in 0..BIDPT { log!("before: [{i}] val: {} token: {}", bidvalArrNew[i], tokensArrNew[i]); }
+        // for i in 0..slotCount { log!("before: [{i}] val: {} token: {}", bidvalArrNew[i], tokensArrNew[i]); }
         sortBidding(&mut bidvalArrNew, &mut tokensArr);
-        // for i in 0..BIDPT { log!("after: [{i}] val: {} token: {}", bidvalArrNew[i], tokensArrNew[i]); }
+        // for i in 0..slotCount { log!("after: [{i}] val: {} token: {}", bidvalArrNew[i], tokensArrNew[i]); }
 
-        let mut contentRec: &mut ContentRec = self.get_content_by_key_unguarded(&contentKey);
         contentRec.bidvalArr = bidvalArrNew;
         contentRec.tokensArr = tokensArr;
+        if !usedUpValue.is_zero() {
+            contentRec.bidToken = Some(token.to_string());
+        }
 
-        // rem lock unlock
-
-        self.lock_end(&pre);
+        //.rem: anti-sniping, a winning bid close to the end extends the window
+        let extendedEnd = env::block_timestamp() + contentRec.snipeMinutes as u64 * NS_PER_MINUTE;
+        if let Some(end) = contentRec.biddingEnd {
+            if !usedUpValue.is_zero() && extendedEnd > end.0 {
+                contentRec.biddingEnd = Some(U64(extendedEnd));
+                log!("{pre} {FgCyan}bidding end extended to {}{R}", extendedEnd);
+            }
+        }
+        self.save_content(&contentKey, &contentRec);
 
-        self.emit_content_bid(&contentKey);
+        self.emit_content_bid(&contentKey, token);
         //.fix: check balance - balance at start, if diff > .1 -> warn
+        selfRefund
+    }
+
+    //#f04: earnings ledger
+    //.rem  bids and licences only credit here, recipients withdraw with claim_earnings
+
+    fn credit_earnings(&mut self, accountId: &str, token: &str, amount: Amount) {
+        if amount.is_zero() {
+            return;
+        }
+        let mut balances = self
+            .earnings
+            .get(&accountId.to_string())
+            .unwrap_or_default();
+        *balances.entry(token.to_string()).or_default() += amount;
+        self.earnings.insert(&accountId.to_string(), &balances);
+        log!(
+            "--credit_earnings: ->{} amount: {H}{}{R} {}",
+            accountId,
+            amount,
+            token
+        );
+    }
+
+    pub fn get_earnings(&self, account_id: AccountId) -> HashMap<String, Amount> {
+        self.earnings
+            .get(&account_id.to_string())
+            .unwrap_or_default()
+    }
+
+    //.rem  token is "near" or the FT contract id, FT dust below 1 unit stays for the next claim
+    pub fn claim_earnings(&mut self, token: String) -> Promise {
+        let accountId = env::predecessor_account_id().to_string();
+        let mut balances = self.earnings.get(&accountId).unwrap_or_default();
+        let balance = balances.get(&token).copied().unwrap_or_default();
+
+        let claimed = if token == NEAR_TOKEN {
+            balance
+        } else {
+            //.rem  no accepted token check, a token removed from the list stays claimable
+            let decimals = self.token_decimals(&token);
+            Amount::from_units(balance.to_units(decimals), decimals)
+        };
+        require!(
+            !claimed.is_zero(),
+            format!("Nothing to claim in {} for {}", token, accountId)
+        );
+
+        let rest = balance - claimed;
+        if rest.is_zero() {
+            balances.remove(&token);
+        } else {
+            balances.insert(token.clone(), rest);
+        }
+        if balances.is_empty() {
+            self.earnings.remove(&accountId);
+        } else {
+            self.earnings.insert(&accountId, &balances);
+        }
+
+        CmgEvent::EarningsClaimed {
+            account_id: accountId.clone(),
+            token: token.clone(),
+            amount: claimed,
+        }
+        .emit();
+        self.transfer_funds("claimEarnings", &accountId, &token, claimed)
     }
 
-    //#f04: all coto transfers call this one
+    //#f04: all outgoing transfers call this one
+    //.rem  the callback puts the amount back into the ledger if the transfer fails
 
-    fn transfer_funds(&mut self, msg: &str, to: &str, amount: u128) -> Promise {
+    fn transfer_funds(&mut self, msg: &str, to: &str, token: &str, amount: Amount) -> Promise {
         log!(
-            "{LightBlue}transfer_funds called {}->{}->{} {}_COTO {}{R}",
+            "{LightBlue}transfer_funds called {}->{}->{} {} {} {}{R}",
             env::predecessor_account_id(),
             env::current_account_id(),
             to,
             amount,
+            token,
             msg
         );
-        // gas_log("pre::coto_transfer ");
-        let coto = U128(amount / 1_000_000_000_000_000_000_000);
-
-        Promise::new(String::from("ft1.setalosas.testnet").parse().unwrap()).function_call(
-            String::from("coto_transfer"),
-            json!({ "receiver_id": String::from(to), "amount": coto })
-                .to_string()
-                .as_bytes()
-                .to_vec(),
-            1,        // one yocto
-            COTO_GAS, // 2 Tgas
+        // gas_log("pre::ft_transfer ");
+        let transfer = if token == NEAR_TOKEN {
+            Promise::new(String::from(to).parse().unwrap()).transfer(amount.yocto())
+        } else {
+            //.rem  the standard method, coto_transfer is only on the COTO contract
+            Promise::new(String::from(token).parse().unwrap()).function_call(
+                String::from("ft_transfer"),
+                json!({ "receiver_id": String::from(to), "amount": amount.to_units(self.token_decimals(token)) })
+                    .to_string()
+                    .as_bytes()
+                    .to_vec(),
+                1, // one yocto
+                FT_TRANSFER_GAS,
+            )
+        };
+
+        transfer.then(
+            ext_self::ext(env::current_account_id())
+                .with_static_gas(RESOLVE_GAS)
+                .resolve_transfer_funds(String::from(to), String::from(token), amount),
         )
     }
 
+    #[private]
+    pub fn resolve_transfer_funds(
+        &mut self,
+        accountId: String,
+        token: String,
+        amount: Amount,
+    ) -> bool {
+        let success = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        if !success {
+            log!(
+                "{FgRed}transfer_funds failed ->{} {} {}, credited back{R}",
+                accountId,
+                amount,
+                token
+            );
+            self.credit_earnings(&accountId, &token, amount);
+        }
+        CmgEvent::TransferFundsResult {
+            account_id: accountId,
+            token,
+            amount,
+            status: if success {
+                TransferStatus::Transferred
+            } else {
+                TransferStatus::Recredited
+            },
+        }
+        .emit();
+        success
+    }
+
     //#f90: slot aggregation
 
     fn addToAgg(&self, ownerId: String, pt: i32, aggMap: &mut HashMap<String, i32>) {
@@ -903,16 +2270,18 @@ impl Contract {
 
     fn get_content_owners_from_arr_internal(
         &self,
-        tokensArr: [usize; BIDPT],
+        tokensArr: &[usize],
         creatorId: String,
+        creatorPt: u32,
+        slotPt: u32,
     ) -> HashMap<String, i32> {
         // log!("{FgLime}buy_licence: content found: {}{R}", contentKey.key);
         let mut aggMap: HashMap<String, i32> = HashMap::new();
 
-        let bidderArr: [String; BIDPT] = self.get_nft_owners_for(tokensArr);
-        self.addToAgg(creatorId, 100 - BIDPT as i32, &mut aggMap);
-        for i in 0..BIDPT {
-            self.addToAgg(bidderArr[i].clone(), 1, &mut aggMap);
+        let bidderArr: Vec<String> = self.get_nft_owners_for(tokensArr.to_vec());
+        self.addToAgg(creatorId, creatorPt as i32, &mut aggMap);
+        for bidder in bidderArr {
+            self.addToAgg(bidder, slotPt as i32, &mut aggMap);
         }
         aggMap
     }
@@ -923,7 +2292,7 @@ impl Contract {
         creatorId: String,
         timestamp: u64,
     ) -> HashMap<String, i32> {
-        let contentKey = Self::create_content_key(&contentId, &creatorId, timestamp);
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
         let someContent = self.get_content_or_none(&contentKey);
         require!(
             someContent.is_some(),
@@ -931,7 +2300,12 @@ impl Contract {
         );
 
         let content = someContent.unwrap();
-        self.get_content_owners_from_arr_internal(content.tokensArr, creatorId)
+        self.get_content_owners_from_arr_internal(
+            &content.tokensArr,
+            creatorId,
+            content.creatorPt,
+            content.slotPt,
+        )
     }
 
     // rem dup, modified for values
@@ -940,39 +2314,41 @@ impl Contract {
         &self,
         ownerId: String,
         pt: i32,
-        val: f32,
-        aggMap: &mut HashMap<String, (i32, f32)>,
+        val: Amount,
+        aggMap: &mut HashMap<String, (i32, Amount)>,
     ) {
         let rust1 = ownerId.clone();
         let rust2 = ownerId;
-        let oldptval = aggMap.get(&rust1).unwrap_or(&(0, 0.0));
+        let oldptval = aggMap.get(&rust1).unwrap_or(&(0, Amount::ZERO));
         let newptval = (oldptval.0 + pt, oldptval.1 + val);
         aggMap.remove(&rust2);
         aggMap.insert(rust2.clone(), newptval);
     }
 
-    fn get_content_owners_internal(&self, content: &ContentRec) -> HashMap<String, (i32, f32)> {
-        let tokensArr = content.tokensArr;
+    fn get_content_owners_internal(&self, content: &ContentRec) -> HashMap<String, (i32, Amount)> {
         let bidvalArr = &content.bidvalArr;
-        let bidderArr: [String; BIDPT] = self.get_nft_owners_for(tokensArr);
+        let bidderArr: Vec<String> = self.get_nft_owners_for(content.tokensArr.clone());
 
-        let mut aggMap: HashMap<String, (i32, f32)> = HashMap::new();
+        let mut aggMap: HashMap<String, (i32, Amount)> = HashMap::new();
         self.addToAggPtVal(
             content.creatorId.clone(),
-            100 - BIDPT as i32,
-            0.0,
+            content.creatorPt as i32,
+            Amount::ZERO,
             &mut aggMap,
         );
 
-        for i in 0..BIDPT {
-            self.addToAggPtVal(bidderArr[i].clone(), 1, bidvalArr[i], &mut aggMap);
+        for (bidder, &bidval) in bidderArr.iter().zip(bidvalArr.iter()) {
+            self.addToAggPtVal(bidder.clone(), content.slotPt as i32, bidval, &mut aggMap);
         }
         aggMap
     }
 
     //.fix  make it pub -> contentKey cannot be ref
 
-    fn get_content_owners_pts_vals(&self, contentKey: &ContentKey) -> HashMap<String, (i32, f32)> {
+    fn get_content_owners_pts_vals(
+        &self,
+        contentKey: &ContentKey,
+    ) -> HashMap<String, (i32, Amount)> {
         let someContent = self.get_content_or_none(contentKey);
         require!(
             someContent.is_some(),
@@ -980,85 +2356,228 @@ impl Contract {
         );
         let content = someContent.unwrap();
 
-        self.get_content_owners_internal(content)
+        self.get_content_owners_internal(&content)
+    }
+
+    fn assert_licensable(content: &ContentRec) {
+        require!(
+            content.status.is_licensable(),
+            format!("Content is {:?}, not open for licences", content.status)
+        );
+    }
+
+    //.rem  the configured share of the current bids, or minLicencePrice for content nobody has bid on yet
+    fn licence_min_price(&self, content: &ContentRec) -> Amount {
+        let sum: Amount = content.bidvalArr.iter().copied().sum();
+        if sum.is_zero() {
+            self.config.minLicencePrice
+        } else {
+            sum.percent(self.config.licencePricePt as u128)
+        }
+    }
+
+    //.rem  (treasury fee, [(owner, pt, payment)]) of a licence sold at `price`
+    //.rem  rounding leftovers go to the creator, so the payments add up to the price
+    fn licence_payments(
+        &self,
+        content: &ContentRec,
+        price: Amount,
+    ) -> (Amount, Vec<(String, i32, Amount)>) {
+        let aggMap: HashMap<String, i32> = self.get_content_owners_from_arr_internal(
+            &content.tokensArr,
+            content.creatorId.clone(),
+            content.creatorPt,
+            content.slotPt,
+        );
+
+        let treasuryShare = price.percent(self.config.treasuryPt as u128);
+        let remainingPrice = price - treasuryShare;
+        let mut payments: Vec<(String, i32, Amount)> = aggMap
+            .into_iter()
+            .map(|(owner, pt)| (owner, pt, remainingPrice.percent(pt as u128)))
+            .collect();
+        let paid: Amount = payments.iter().map(|p| p.2).sum();
+        if let Some(creatorPayment) = payments.iter_mut().find(|p| p.0 == content.creatorId) {
+            creatorPayment.2 += remainingPrice - paid;
+        }
+        (treasuryShare, payments)
     }
 
-    // public interface buy_licence
+    //.pub  licence quote
+    //.rem  minimum price and payout split a buy_licence at `price` (default: the minimum) would make
 
-    #[payable]
-    pub fn buy_licence(
-        &mut self,
+    pub fn quote_licence(
+        &self,
         contentId: String,
         creatorId: String,
         timestamp: u64,
-        scoutId: String,
-        price: f32,
-    ) {
-        //.todo  must create new content item if no bids yet
+        price: Option<Amount>,
+    ) -> LicenceQuote {
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
+        let content = self.get_content_by_key(&contentKey);
+        Self::assert_licensable(&content);
+        let minPrice = self.licence_min_price(&content);
+        let price = price.unwrap_or(minPrice);
+        require!(
+            price >= minPrice,
+            format!("Price ({price}) must be >= minimum price {minPrice}")
+        );
 
-        let contentKey = Self::create_content_key(&contentId, &creatorId, timestamp);
-        let contentExisted = self.confirm_content_by_key(&contentKey);
-        let content: &ContentRec = self.get_content_by_key_unguarded(&contentKey);
-        log!("{FgLime}buy_licence: content found: {}{R}", contentKey.key);
+        let (treasuryFee, payments) = self.licence_payments(&content, price);
+        let mut payouts: Vec<LicencePayout> = payments
+            .into_iter()
+            .map(|(ownerId, pt, amount)| LicencePayout {
+                ownerId,
+                pt,
+                amount,
+            })
+            .collect();
+        payouts.sort_by(|a, b| b.pt.cmp(&a.pt).then_with(|| a.ownerId.cmp(&b.ownerId)));
 
-        //.todo: check? no
-        //.todo  check for minimal deposit (contentExisted!)
+        LicenceQuote {
+            minPrice,
+            price,
+            token: content.bidToken,
+            treasuryFee,
+            payouts,
+        }
+    }
+
+    // public interface buy_licence (paid in NEAR)
 
-        let deposit = env::attached_deposit();
-        let depositNEAR = (deposit as f32) / 1E24;
+    #[payable]
+    pub fn buy_licence(
+        &mut self,
+        contentId: String,
+        creatorId: String,
+        timestamp: u64,
+        scoutId: String,
+        price: Amount,
+    ) {
+        let deposit = Amount::from_yocto(env::attached_deposit());
         log!(
             "{FgLime}buy_licence: attached deposit: {} near by {}{R}",
-            depositNEAR,
+            deposit,
             scoutId
         );
         require!(
-            depositNEAR >= price,
-            format!("Requires attached deposit of at least {} NEAR", depositNEAR)
+            deposit >= price,
+            format!("Requires attached deposit of at least {} NEAR", price)
         );
+        self.buy_licence_internal(contentId, creatorId, timestamp, scoutId, price, NEAR_TOKEN);
+    }
 
-        let sum = if contentExisted {
-            let mut sum = 0.0;
-            let bidvalArr = content.bidvalArr;
-            for i in 0..BIDPT {
-                sum += bidvalArr[i];
-            }
-            //3.0 * sum
-            1.0 * sum
-        } else {
-            0.05 // from config
-        };
+    //.rem  shares are credited in the token the licence was paid with
+    fn buy_licence_internal(
+        &mut self,
+        contentId: String,
+        creatorId: String,
+        timestamp: u64,
+        scoutId: String,
+        price: Amount,
+        token: &str,
+    ) {
+        //.todo  must create new content item if no bids yet
+        self.assert_not_paused(PauseScope::Licences);
+
+        let contentKey = Self::parse_content_key(&contentId, &creatorId, timestamp);
+        let content = self.get_content_by_key(&contentKey);
+        log!("{FgLime}buy_licence: content found: {}{R}", contentKey.key);
+        Self::assert_licensable(&content);
+        //.rem  the minimum price is the sum of the bids, which is only comparable in their token
+        if let Some(bidToken) = &content.bidToken {
+            require!(
+                bidToken == token,
+                format!(
+                    "Licences of {} are paid in {}, not {}",
+                    contentId, bidToken, token
+                )
+            );
+        }
+        //.rem  the shares go to the current owners of the content NFTs, receivers of NFTs in flight too
+
+        let minPrice = self.licence_min_price(&content);
         require!(
-            price >= sum,
-            format!("Price ({price}) must be >= minimum price {sum}")
+            price >= minPrice,
+            format!("Price ({price}) must be >= minimum price {minPrice}")
         );
 
-        let tokensArr = content.tokensArr;
-        // log!("{:#?}", tokensArr);
-        let aggMap: HashMap<String, i32> =
-            self.get_content_owners_from_arr_internal(tokensArr, creatorId);
-        // log!("{:#?}", aggMap);
-
-        let treasuryShare = price * 0.1;
+        let (treasuryShare, payments) = self.licence_payments(&content, price);
         log!(
-            "{FgLime}buy_licence: payment 10% = {H}{}{FgLime} NEAR ->platform {R}",
-            treasuryShare
+            "{FgLime}buy_licence: payment 10% = {H}{}{FgLime} {} ->platform {R}",
+            treasuryShare,
+            token
+        );
+        let treasuryId = self.config.treasuryId.to_string();
+        self.credit_earnings(&treasuryId, token, treasuryShare);
+        Self::emit_payout(
+            &contentKey,
+            &treasuryId,
+            token,
+            treasuryShare,
+            PayoutReason::LicenceTreasury,
         );
-        self.general_pay_near(TREASURY_ID, treasuryShare);
 
-        let remainingPrice = price * 0.9;
-        for (owner, pt) in aggMap {
-            let ownerPayment = remainingPrice * (pt as f32) / 100.;
+        for (owner, pt, ownerPayment) in payments {
             log!(
-                "{FgLime}buy_licence: payment {}% = {H}{}{FgLime} NEAR ->{}{R}",
+                "{FgLime}buy_licence: payment {}% = {H}{}{FgLime} {} ->{}{R}",
                 pt,
                 ownerPayment,
+                token,
                 owner
             );
-            self.general_pay_near(&owner, ownerPayment);
+            self.credit_earnings(&owner, token, ownerPayment);
+            Self::emit_payout(
+                &contentKey,
+                &owner,
+                token,
+                ownerPayment,
+                PayoutReason::LicenceOwner,
+            );
         }
-        self.create_licence_nft(&contentKey, &env::predecessor_account_id(), price);
-        self.emit_content_licensing(&contentKey, &scoutId, price);
-        // fix tokenid ^
+        let scoutAccountId: AccountId = scoutId
+            .parse()
+            .unwrap_or_else(|_| env::panic_str(&format!("Invalid account id {}", scoutId)));
+        let tokenId =
+            self.create_licence_nft(&contentKey, &content.metadata, &scoutAccountId, price);
+        CmgEvent::Licence {
+            content_id: contentKey.key,
+            token_id: tokenId,
+            buyer: scoutAccountId,
+            price,
+            token: token.to_string(),
+        }
+        .emit();
+    }
+
+    //.pub  licence NFTs
+    //.rem  licences are soulbound: the collection has no transfer or approval methods,
+    //.rem  a licence stays with the account that bought it
+
+    pub fn licence_nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.licenceTokens.nft_token(token_id)
+    }
+
+    pub fn licence_nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        let licences = match self.licencesPerOwner.get(&account_id) {
+            Some(licences) => licences,
+            None => return Vec::new(),
+        };
+        licences
+            .iter()
+            .skip(from_index.map_or(0, |i| i.0 as usize))
+            .take(limit.map_or(usize::MAX, |l| l as usize))
+            .filter_map(|tokenId| self.licenceTokens.nft_token(tokenId))
+            .collect()
+    }
+
+    pub fn licence_nft_metadata(&self) -> NFTContractMetadata {
+        self.licenceMetadata.get().unwrap()
     }
 
     // int system hacks and overrides
@@ -1078,76 +2597,70 @@ impl Contract {
         receiver_id: AccountId,
         token_metadata: TokenMetadata,
     ) -> Token {
+        //.rem  internal_mint skips the owner check of self.tokens.mint
+        self.assert_role(Role::Admin, "mint");
         self.tokens
             .internal_mint(token_id, receiver_id, Some(token_metadata))
     }
 
-    // rem scout reinbursement method
+    // rem scout reinbursement method (credited, claimed later)
     //
-    fn payback_scout(&mut self, token_id: &TokenId, scoutRefund: f32) {
-        //.todo  f32 is not good enough
+    fn payback_scout(&mut self, token_id: &TokenId, scoutRefund: Amount, token: &str) -> AccountId {
         let account_id = self
             .tokens
             .owner_by_id
             .get(token_id)
             .unwrap_or_else(|| env::panic_str(&format!("Token {} not found", token_id)));
 
-        let refund = (scoutRefund * 1E24) as u128;
         log!(
-            "--payback_scout: from current owner ->{} amount: {H}{}{R} NEAR",
+            "--payback_scout: from current owner ->{} amount: {H}{}{R} {}",
             account_id,
-            scoutRefund
+            scoutRefund,
+            token
         );
-        let accountId = &account_id.to_string(); // &String::from(&account_id);
-        Promise::new(account_id).transfer(refund);
-        //self.emit_transfer_funds("scout_payback", accountId, &accountId, scoutRefund);
-        self.emit_transfer_funds("scout_payback", accountId, accountId, scoutRefund);
+        if !self.hold_refund(token_id, &account_id, token, scoutRefund) {
+            self.credit_earnings(account_id.as_str(), token, scoutRefund);
+        }
+        account_id
     }
     // let amount: u128 = 1_000_000_000_000_000_000_000_000; // 1 $NEAR as yoctoNEAR
 
-    // rem creator payment method
+    // rem creator payment method (credited, claimed later)
     //
-    fn pay_creator(&mut self, creatorId: &str, creatorRefund: f32) {
-        let refund = (creatorRefund * 1E24) as u128;
-        let creator_id: AccountId = String::from(creatorId).parse().unwrap();
+    fn pay_creator(
+        &mut self,
+        contentKey: &ContentKey,
+        creatorId: &str,
+        creatorRefund: Amount,
+        token: &str,
+    ) {
+        let (creatorShare, treasuryShare) =
+            split_creator_share(creatorRefund, self.config.treasuryPt);
+        let treasuryId = self.config.treasuryId.to_string();
         log!(
-            "--pay_creator: from bidder to creator ->{} amount: {H}{}{R} NEAR",
+            "--pay_creator: from bidder to creator ->{} amount: {H}{}{R} {}",
             creatorId,
-            creatorRefund
+            creatorRefund,
+            token
         );
 
-        //Promise::new(creator_id).transfer(refund * 9 / 10);
-        self.transfer_funds("payCreator", creatorId, refund * 9 / 10);
-
-        self.emit_transfer_funds("bid_creator", creatorId, creatorId, creatorRefund * 0.9);
-
-        //Promise::new(String::from(TREASURY_ID).parse().unwrap()).transfer(refund * 1 / 10);
-        self.transfer_funds("payCreator", TREASURY_ID, refund / 10);
-
-        self.emit_transfer_funds("bid_share", creatorId, creatorId, creatorRefund * 0.1);
-    }
-
-    // rem general_pay_near
-    //
-    fn general_pay_near(&self, accountId: &str, near: f32) {
-        let yoctoNear = (near * 1E24) as u128;
-        let account_id: AccountId = String::from(accountId).parse().unwrap();
-        // log!("--general_pay_near: ->{} amount: {H}{}{R} NEAR", accountId, near);
-        Promise::new(account_id).transfer(yoctoNear);
-        self.emit_transfer_funds("general", accountId, accountId, near); //.fix: not here
-    }
+        self.credit_earnings(creatorId, token, creatorShare);
+        Self::emit_payout(
+            contentKey,
+            creatorId,
+            token,
+            creatorShare,
+            PayoutReason::BidCreator,
+        );
 
-    // rem modded version of internal_transfer (no approvals, no event log)
-    // Transfer from current owner to receiver_id, return previous owner and approvals.
-    pub fn internal_transfer_mod(
-        &mut self,
-        sender_id: &AccountId,
-        receiver_id: &AccountId,
-        #[allow(clippy::ptr_arg)] token_id: &TokenId,
-    ) {
-        self.tokens
-            .internal_transfer_unguarded(token_id, sender_id, receiver_id);
-        // NonFungibleToken::emit_transfer(&owner_id, receiver_id, token_id, sender_id, memo);
+        self.credit_earnings(&treasuryId, token, treasuryShare);
+        Self::emit_payout(
+            contentKey,
+            &treasuryId,
+            token,
+            treasuryShare,
+            PayoutReason::BidTreasury,
+        );
     }
 
     // rem modified version of the nft_transfer method of near-contract-standards
@@ -1164,7 +2677,6 @@ impl Contract {
 
         log!("--nft_transfer_mod: {H}#{}{R} ->{}", token_id, receiver_id);
         // self.tokens.internal_transfer(&sender_id, &receiver_id, token_id, None, None);
-        // self.internal_transfer_mod(&sender_id, &receiver_id, token_id);
         self.tokens
             .internal_transfer_unguarded(token_id, &sender_id, receiver_id);
     }
@@ -1173,8 +2685,15 @@ impl Contract {
     // NFT (of tokenId) will be transferred to biddingScout (from creator or other scout)
     // if refund > 0, the prev scout + creator should be reimbursed (split)
     // if refund = 0, only the creator
-    fn rebid(&mut self, token_id: &TokenId, biddingScoutId: &AccountId, scoutRefund: f32) {
-        if scoutRefund > 0.0 {
+    fn rebid(
+        &mut self,
+        contentKey: &ContentKey,
+        token_id: &TokenId,
+        biddingScoutId: &AccountId,
+        scoutRefund: Amount,
+        token: &str,
+    ) {
+        if !scoutRefund.is_zero() {
             // only for scout, not for OG creator
             log!(
                 "rebid: #{} ->{} scoutRefund: {}",
@@ -1182,47 +2701,76 @@ impl Contract {
                 biddingScoutId,
                 scoutRefund
             );
-            self.payback_scout(token_id, scoutRefund); // this needs to be called first, before the transfer
+            let previousOwner = self.payback_scout(token_id, scoutRefund, token); // this needs to be called first, before the transfer
+            CmgEvent::Outbid {
+                content_id: contentKey.key.clone(),
+                token_id: token_id.clone(),
+                previous_owner: previousOwner,
+                new_owner: biddingScoutId.clone(),
+                refund: scoutRefund,
+                token: token.to_string(),
+            }
+            .emit();
         } else {
             log!("rebid: 1st bid, no reimbursement.");
         }
         self.nft_transfer_mod(token_id, biddingScoutId);
     }
+}
 
-    // debug / test methods
-
-    #[payable]
-    pub fn test_buy(&mut self, ix: usize, price: f32) {
-        let cont = testContentData[ix];
-        self.buy_licence(
-            String::from(cont.0),
-            String::from(cont.1),
-            cont.2,
-            String::from(env::predecessor_account_id()),
-            price,
-        );
-        // self.showContentListWithBidding();
+//.rem  `publicKey` is a near_sdk::PublicKey, its first byte is the curve type
+fn verify_ed25519(publicKey: &PublicKey, message: &[u8], signature: &[u8]) -> bool {
+    use ed25519_dalek::Verifier;
+    let publicKey = match ed25519_dalek::PublicKey::from_bytes(&publicKey.as_bytes()[1..]) {
+        Ok(publicKey) => publicKey,
+        Err(_) => return false,
+    };
+    match ed25519_dalek::Signature::from_bytes(signature) {
+        Ok(signature) => publicKey.verify(message, &signature).is_ok(),
+        Err(_) => false,
     }
-    #[payable]
-    pub fn test_bid(&mut self, ix: usize, value: f32, pt: i32) {
-        let content = testContentData[ix];
-        self.add_bid_coto(
-            String::from(content.0),
-            String::from(content.1),
-            content.2,
-            String::from(env::predecessor_account_id()),
-            value,
-            pt,
-        );
-        self.showContentListWithBidding();
+}
+
+//.rem  storage added by a call is paid from its attached deposit, the rest goes back to the caller
+//.rem  (as the refund_deposit of near-contract-standards, which is private there)
+fn refund_deposit(storageUsed: u64) {
+    let required = env::storage_byte_cost() * storageUsed as u128;
+    let attached = env::attached_deposit();
+    require!(
+        required <= attached,
+        format!("Must attach {} yoctoNEAR to cover storage", required)
+    );
+    let refund = attached - required;
+    if refund > 1 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
     }
 }
 
-fn ugas() -> String {
-    format!("gasUsed: {} G ", env::used_gas().0 / 1_000_000_000)
+//.rem  an optional value in a transfer message must be the amount actually transferred
+//.rem  a value below the smallest unit of the token could never be transferred, nor paid out
+fn check_declared_value(declared: Option<Amount>, transferred: Amount, decimals: u8) {
+    if let Some(declared) = declared {
+        require!(
+            Amount::from_units(declared.to_units(decimals), decimals) == declared,
+            format!(
+                "Declared value {} has more than the {} decimals of the token",
+                declared, decimals
+            )
+        );
+        require!(
+            declared == transferred,
+            format!(
+                "Declared value {} does not match the transferred {}",
+                declared, transferred
+            )
+        );
+    }
 }
-fn gas_log(msg: &str) {
-    log!("{Pink}{msg}{}{R}", ugas());
+
+//.rem  (creator, treasury) parts of what a bid pays on top of the displaced value
+fn split_creator_share(amount: Amount, treasuryPt: u32) -> (Amount, Amount) {
+    let treasuryShare = amount.percent(treasuryPt as u128);
+    (amount - treasuryShare, treasuryShare)
 }
 
 fn tokenId2Str(tokenId: usize) -> String {
@@ -1232,7 +2780,69 @@ fn tokenId2URL(tokenId: usize) -> String {
     format!("https://img.mork.work/lj/{:04}.jpg", (tokenId % 1000) + 819)
 }
 
-near_contract_standards::impl_non_fungible_token_core!(Contract, tokens);
+//.rem  impl_non_fungible_token_core! with the transfers pause check and the settlements
+#[near_bindgen]
+impl NonFungibleTokenCore for Contract {
+    #[payable]
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        self.assert_not_paused(PauseScope::Transfers);
+        self.tokens
+            .nft_transfer(receiver_id, token_id, approval_id, memo)
+    }
+
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        self.assert_not_paused(PauseScope::Transfers);
+        self.start_settlement(&token_id);
+        self.tokens
+            .nft_transfer_call(receiver_id, token_id, approval_id, memo, msg)
+    }
+
+    fn nft_token(&self, token_id: TokenId) -> Option<Token> {
+        self.tokens.nft_token(token_id)
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for Contract {
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        //.rem  as in the standard resolve: anything but `false` from nft_on_transfer returns it
+        let returned = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            _ => true,
+        };
+        self.end_settlement(&token_id, &previous_owner_id, returned);
+        self.tokens.nft_resolve_transfer(
+            previous_owner_id,
+            receiver_id,
+            token_id,
+            approved_account_ids,
+        )
+    }
+}
+
 near_contract_standards::impl_non_fungible_token_approval!(Contract, tokens);
 near_contract_standards::impl_non_fungible_token_enumeration!(Contract, tokens);
 
@@ -1242,25 +2852,33 @@ impl NonFungibleTokenMetadataProvider for Contract {
         self.metadata.get().unwrap()
     }
 }
-const Pink: &str = "\x1b[38;2;255;100;125m";
-const Bright: &str = "\x1b[1m";
-const FgRed: &str = "\x1b[31m";
-const FgGreen: &str = "\x1b[32m";
-const FgBrightGreen: &str = "\x1b[1m\x1b[32m";
-const FgYellow: &str = "\x1b[33m";
-const H: &str = "\x1b[33m";
-const FgBlue: &str = "\x1b[38;5;027m"; //'\x1b[34m";
-const FgLime: &str = "\x1b[38;5;148m"; // '\x1b[35m";
-const FgCyan: &str = "\x1b[38;5;086m"; // '\x1b[36m";
-const FgWhite: &str = "\x1b[37m";
-const R: &str = "\x1b[0m";
-const FgOrange: &str = "\x1b[38;5;202m";
-const FgPurple: &str = "\x1b[38;5;127m";
-const FgA: &str = "\x1b[38;5;163m"; // magentish
-const FgZ: &str = "\x1b[38;5;039m";
-const FgT: &str = "\x1b[38;5;035m"; // GGB
-const FgX: &str = "\x1b[38;5;042m"; // GGB brighter
-const LightBlue: &str = "\x1b[38;2;150;150;255m";
+//.rem  ANSI colours for the dev logs, empty strings in production builds
+const fn color(code: &'static str) -> &'static str {
+    if cfg!(feature = "dev") {
+        code
+    } else {
+        ""
+    }
+}
+const Pink: &str = color("\x1b[38;2;255;100;125m");
+const Bright: &str = color("\x1b[1m");
+const FgRed: &str = color("\x1b[31m");
+const FgGreen: &str = color("\x1b[32m");
+const FgBrightGreen: &str = color("\x1b[1m\x1b[32m");
+const FgYellow: &str = color("\x1b[33m");
+const H: &str = color("\x1b[33m");
+const FgBlue: &str = color("\x1b[38;5;027m"); //'\x1b[34m";
+const FgLime: &str = color("\x1b[38;5;148m"); // '\x1b[35m";
+const FgCyan: &str = color("\x1b[38;5;086m"); // '\x1b[36m";
+const FgWhite: &str = color("\x1b[37m");
+const R: &str = color("\x1b[0m");
+const FgOrange: &str = color("\x1b[38;5;202m");
+const FgPurple: &str = color("\x1b[38;5;127m");
+const FgA: &str = color("\x1b[38;5;163m"); // magentish
+const FgZ: &str = color("\x1b[38;5;039m");
+const FgT: &str = color("\x1b[38;5;035m"); // GGB
+const FgX: &str = color("\x1b[38;5;042m"); // GGB brighter
+const LightBlue: &str = color("\x1b[38;2;150;150;255m");
 
 // --------------------NOT VALID FROM HERE (orig nft placeholder tests):--------------------------
 
@@ -1273,8 +2891,20 @@ mod tests {
     use std::collections::HashMap;
 
     use super::*;
+    use crate::events::{EVENT_STANDARD, EVENT_VERSION};
+    use near_sdk::serde_json;
 
     const MINT_STORAGE_COST: u128 = 5870000000000000000000;
+    const REGISTER_DEPOSIT: u128 = E24;
+
+    // attaches the storage deposit for `call`, keeping the deposit of `context` for later calls
+    fn paying_storage<T>(context: &mut VMContextBuilder, call: impl FnOnce() -> T) -> T {
+        let deposit = context.context.attached_deposit;
+        testing_env!(context.attached_deposit(REGISTER_DEPOSIT).build());
+        let result = call();
+        testing_env!(context.attached_deposit(deposit).build());
+        result
+    }
 
     fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
@@ -1285,6 +2915,50 @@ mod tests {
         builder
     }
 
+    fn coto_id() -> AccountId {
+        DEFAULT_COTO_ID.parse().unwrap()
+    }
+
+    // registers with the default layout as `creatorId`, keeping the predecessor of `context`
+    fn register(
+        context: &mut VMContextBuilder,
+        contract: &mut Contract,
+        creatorId: &str,
+        contentId: &str,
+        timestamp: u64,
+    ) {
+        let predecessor = context.context.predecessor_account_id.clone();
+        testing_env!(context
+            .predecessor_account_id(creatorId.parse().unwrap())
+            .build());
+        paying_storage(context, || {
+            contract.register_content(
+                contentId.into(),
+                timestamp,
+                DEFAULT_SLOT_COUNT,
+                DEFAULT_SLOT_PT,
+                None,
+            )
+        });
+        testing_env!(context.predecessor_account_id(predecessor).build());
+    }
+
+    // (creator key for set_creator_key, signer of content approvals)
+    fn creator_key() -> (PublicKey, impl Fn(&ContentApproval) -> Base64VecU8) {
+        use ed25519_dalek::{ExpandedSecretKey, SecretKey};
+        use std::convert::TryFrom;
+        let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        let mut keyBytes = vec![0u8]; // CurveType::ED25519
+        keyBytes.extend_from_slice(public.as_bytes());
+        let sign = move |approval: &ContentApproval| {
+            let signature =
+                ExpandedSecretKey::from(&secret).sign(&approval.to_signed_bytes(), &public);
+            Base64VecU8(signature.to_bytes().to_vec())
+        };
+        (PublicKey::try_from(keyBytes).unwrap(), sign)
+    }
+
     fn sample_token_metadata() -> TokenMetadata {
         TokenMetadata {
             title: Some("Olympus Mons".into()),
@@ -1496,16 +3170,23 @@ mod tests {
         testing_env!(context
             .storage_usage(env::storage_usage())
             .attached_deposit(MINT_STORAGE_COST)
-            .predecessor_account_id(accounts(0))
+            .predecessor_account_id(coto_id())
             .build());
 
         const NFT_ID: &str = "85d491b3-18f8-40f6-be33-b83dd749a8a4:creator.testnet:123367777";
         const EXAMPLE_BID: &str =
             "bid:creator.testnet:85d491b3-18f8-40f6-be33-b83dd749a8a4:123367777:10:22.5:";
         const PT: i32 = 10;
-        const COTO_VALUE: f32 = 22.5;
-
-        let res = contract.ft_on_transfer(accounts(0), U128::from(100000_u128), EXAMPLE_BID.into());
+        let cotoValue = Amount::parse_decimal("22.5").unwrap();
+
+        register(
+            &mut context,
+            &mut contract,
+            "creator.testnet",
+            "85d491b3-18f8-40f6-be33-b83dd749a8a4",
+            123367777,
+        );
+        let res = contract.ft_on_transfer(accounts(0), U128::from(22_500_u128), EXAMPLE_BID.into());
 
         match res {
             PromiseOrValue::Promise(_) => panic!("Expected PromiseOrValue::Value"),
@@ -1520,14 +3201,1773 @@ mod tests {
             println!("{}: {:?}", k, v);
         });
 
-        let content = contract.contents.get(NFT_ID).unwrap();
+        let content = contract.load_content(NFT_ID).unwrap();
 
         assert_eq!(content.bidvalArr.len(), 20);
         assert_eq!(content.tokensArr.len(), 20);
 
         let (left, right) = content.bidvalArr.split_at(10);
 
-        assert_eq!(left, vec![0.0_f32; 10]);
-        assert_eq!(right, vec![COTO_VALUE / PT as f32; 10].as_slice());
+        assert_eq!(left, vec![Amount::ZERO; 10]);
+        assert_eq!(right, vec![cotoValue.div_parts(PT as u128); 10].as_slice());
+    }
+
+    #[test]
+    #[should_panic(expected = "to cover storage")]
+    fn test_register_content_pays_storage() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.register_content("c1".into(), 1, 10, 2, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "to cover storage")]
+    fn test_content_metadata_pays_storage() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        let metadata = ContentMetadata {
+            description: Some("a longer description of the content".into()),
+            ..ContentMetadata::default()
+        };
+        contract.set_content_metadata("c1".into(), 1, metadata);
+    }
+
+    #[test]
+    fn test_register_content_layout() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build());
+        let content = paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        assert_eq!(content.creatorPt, 80);
+        assert_eq!(content.tokensArr.len(), 10);
+
+        // 4% of a 2% layout -> 2 slots of 5
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:4:10", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128::from(10_000_u128), msg);
+
+        let content = contract
+            .load_content(&format!("c1:{}:1", accounts(1)))
+            .unwrap();
+        let five = Amount::parse_decimal("5").unwrap();
+        assert_eq!(content.bidvalArr[8..], [five, five]);
+        let owners = contract.get_content_owners("c1".into(), accounts(1).to_string(), 1);
+        assert_eq!(owners[&accounts(1).to_string()], 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Percent must be a multiple of 2%")]
+    fn test_bid_not_matching_layout() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:3:10", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128::from(10_000_u128), msg);
+    }
+
+    #[test]
+    fn test_unused_bid_is_returned() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 2, 2, None)
+        });
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .predecessor_account_id(coto_id())
+            .build());
+        let msg = format!("bid:{}:c1:1:2", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128(10_000), msg);
+
+        // 4% is 2 slots of 5, the one held at 10 is not taken
+        let msg = format!("bid:{}:c1:1:4", accounts(1));
+        match contract.ft_on_transfer(accounts(1), U128(10_000), msg) {
+            PromiseOrValue::Value(value) => assert_eq!(value, U128(5_000)),
+            PromiseOrValue::Promise(_) => panic!("Expected PromiseOrValue::Value"),
+        }
+        assert!(contract.get_earnings(accounts(2)).is_empty());
+    }
+
+    #[test]
+    fn test_json_transfer_message() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(coto_id())
+            .build());
+        register(&mut context, &mut contract, accounts(1).as_str(), "c:1", 1);
+        let msg = TransferMessage::Bid {
+            creatorId: accounts(1).to_string(),
+            contentId: "c:1".into(),
+            timestamp: 1,
+            pt: 2,
+            value: None,
+            beneficiaryId: None,
+        };
+        contract.ft_on_transfer(accounts(1), U128(10_000), msg.to_json());
+        // 2 default slots of 1%
+        let content = contract.get_bidding_state("c:1".into(), 1, accounts(1).to_string());
+        assert_eq!(
+            content.bidvalArr.last(),
+            Some(&Amount::parse_decimal("5").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_sender_and_beneficiary() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        // signed by a relayer, the FT sender is accounts(2)
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .signer_account_id(accounts(5))
+            .predecessor_account_id(coto_id())
+            .build());
+        register(&mut context, &mut contract, accounts(1).as_str(), "c1", 1);
+        let msg = format!("bid:{}:c1:1:2", accounts(1));
+        contract.ft_on_transfer(accounts(2), U128(10_000), msg);
+        let owners = contract.get_content_owners("c1".into(), accounts(1).to_string(), 1);
+        assert_eq!(owners[&accounts(2).to_string()], 2);
+        assert!(!owners.contains_key(&accounts(5).to_string()));
+
+        let msg = TransferMessage::Buy {
+            creatorId: accounts(1).to_string(),
+            contentId: "c1".into(),
+            timestamp: 1,
+            value: None,
+            beneficiaryId: Some(accounts(3)),
+        };
+        contract.ft_on_transfer(accounts(2), U128(10_000), msg.to_json());
+        let licenceId = tokenId2Str(LICENCE_TOKEN_ID_BASE + 1);
+        assert_eq!(
+            contract.licenceTokens.owner_by_id.get(&licenceId),
+            Some(accounts(3))
+        );
+    }
+
+    #[test]
+    fn test_licence_views() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        register(&mut context, &mut contract, accounts(1).as_str(), "c1", 1);
+        register(&mut context, &mut contract, accounts(1).as_str(), "c2", 1);
+
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        for contentId in ["c1", "c2"] {
+            contract.ft_on_transfer(
+                accounts(3),
+                U128(10_000),
+                format!("buy:{}:{}:1", accounts(1), contentId),
+            );
+        }
+        let licences = contract.licence_nft_tokens_for_owner(accounts(3), None, None);
+        assert_eq!(licences.len(), 2);
+        assert!(licences.iter().all(|token| token.owner_id == accounts(3)));
+        let page = contract.licence_nft_tokens_for_owner(accounts(3), Some(U128(1)), Some(5));
+        assert_eq!(page, licences[1..]);
+        assert!(contract
+            .licence_nft_tokens_for_owner(accounts(2), None, None)
+            .is_empty());
+
+        let licenceId = licences[0].token_id.clone();
+        assert_eq!(
+            contract.licence_nft_token(licenceId.clone()),
+            Some(licences[0].clone())
+        );
+        //.rem  soulbound: the content collection, and its transfer methods, do not know it
+        assert_eq!(contract.nft_token(licenceId), None);
+        assert_eq!(contract.licence_nft_metadata().symbol, "CLI");
+    }
+
+    #[test]
+    #[should_panic(expected = "Content c9:bob:1 is not registered")]
+    fn test_unregistered_content_is_rejected() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c9:1:2", accounts(1));
+        contract.ft_on_transfer(accounts(2), U128(10_000), msg);
+    }
+
+    #[test]
+    #[should_panic(expected = "Content c9:Not.Valid:1 is not registered")]
+    fn test_invalid_creator_is_not_registered() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        contract.ft_on_transfer(accounts(2), U128(10_000), "bid:Not.Valid:c9:1:2".into());
+    }
+
+    fn platform_setup() -> (VMContextBuilder, Contract, ContentApproval) {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let config = Config {
+            platformIds: vec![accounts(3)],
+            ..contract.get_config()
+        };
+        contract.set_config(config);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_creator_key(Some(creator_key().0));
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        let approval = ContentApproval {
+            contractId: accounts(0),
+            creatorId: accounts(1),
+            contentId: "c1".into(),
+            timestamp: 1,
+            slotCount: 10,
+            slotPt: 2,
+            metadata: None,
+        };
+        (context, contract, approval)
+    }
+
+    #[test]
+    fn test_register_content_for() {
+        let (mut context, mut contract, approval) = platform_setup();
+        let signature = creator_key().1(&approval);
+        let content = paying_storage(&mut context, || {
+            contract.register_content_for(approval, signature)
+        });
+        assert_eq!(content.creatorId, accounts(1).to_string());
+        assert_eq!(content.creatorPt, 80);
+        let owners = contract.get_content_owners("c1".into(), accounts(1).to_string(), 1);
+        assert_eq!(owners[&accounts(1).to_string()], 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid creator signature")]
+    fn test_register_content_for_tampered() {
+        let (mut context, mut contract, approval) = platform_setup();
+        let signature = creator_key().1(&approval);
+        let approval = ContentApproval {
+            slotCount: 40,
+            ..approval
+        };
+        paying_storage(&mut context, || {
+            contract.register_content_for(approval, signature)
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Only allow-listed platforms can register content for a creator")]
+    fn test_register_content_for_platform_only() {
+        let (mut context, mut contract, approval) = platform_setup();
+        let signature = creator_key().1(&approval);
+        testing_env!(context.predecessor_account_id(accounts(4)).build());
+        paying_storage(&mut context, || {
+            contract.register_content_for(approval, signature)
+        });
+    }
+
+    #[test]
+    fn test_content_metadata() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let metadata = ContentMetadata {
+            title: Some("Sunset".into()),
+            description: Some("Over the hills".into()),
+            media: Some("https://example.com/sunset.jpg".into()),
+            mediaHash: Some(Base64VecU8(vec![1; 32])),
+            reference: None,
+        };
+        let content = paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 2, 10, Some(metadata.clone()))
+        });
+        assert_eq!(content.metadata, metadata);
+
+        let tokenMetadata = |contract: &Contract, tokenId: usize| {
+            contract
+                .nft_token(tokenId2Str(tokenId))
+                .unwrap()
+                .metadata
+                .unwrap()
+        };
+        let creatorToken = tokenMetadata(&contract, content.tokenId);
+        assert_eq!(
+            creatorToken.title,
+            Some(format!("80% of Sunset #{}", tokenId2Str(content.tokenId)))
+        );
+        assert_eq!(creatorToken.description, metadata.description);
+        assert_eq!(creatorToken.media, metadata.media);
+        assert_eq!(creatorToken.media_hash, metadata.mediaHash);
+
+        testing_env!(context
+            .predecessor_account_id(coto_id())
+            .signer_account_id(accounts(3))
+            .build());
+        contract.ft_on_transfer(
+            accounts(3),
+            U128(10_000),
+            format!("buy:{}:c1:1", accounts(1)),
+        );
+        let licence = contract
+            .licenceTokens
+            .nft_token(tokenId2Str(contract.lastContentTokenId))
+            .unwrap();
+        assert!(licence
+            .metadata
+            .unwrap()
+            .title
+            .unwrap()
+            .contains("for Sunset #"));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let content = contract.set_content_metadata("c1".into(), 1, ContentMetadata::default());
+        let creatorToken = tokenMetadata(&contract, content.tokenId);
+        assert_eq!(
+            creatorToken.title,
+            Some(format!(
+                "80% of c1:{}:1 #{}",
+                accounts(1),
+                tokenId2Str(content.tokenId)
+            ))
+        );
+        assert_eq!(creatorToken.media, Some(tokenId2URL(content.tokenId)));
+        let slotToken = tokenMetadata(&contract, content.tokensArr[1]);
+        assert_eq!(
+            slotToken.title,
+            Some(format!(
+                "10% of c1:{}:1 #{}",
+                accounts(1),
+                tokenId2Str(content.tokensArr[1])
+            ))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is not registered")]
+    fn test_content_metadata_creator_only() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.set_content_metadata("c1".into(), 1, ContentMetadata::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Media hash must be 32 bytes")]
+    fn test_content_metadata_media_hash() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let metadata = ContentMetadata {
+            mediaHash: Some(Base64VecU8(vec![1; 4])),
+            ..Default::default()
+        };
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, Some(metadata))
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Description must be at most 1024 bytes")]
+    fn test_content_metadata_length() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        let metadata = ContentMetadata {
+            description: Some("x".repeat(MAX_DESCRIPTION_LEN + 1)),
+            ..Default::default()
+        };
+        paying_storage(&mut context, || {
+            contract.set_content_metadata("c1".into(), 1, metadata)
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Declared value 9 does not match the transferred 10")]
+    fn test_declared_value_must_match() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(coto_id())
+            .build());
+        let msg = format!("bid:{}:c1:1:2:9", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128(10_000), msg);
+    }
+
+    #[test]
+    #[should_panic(expected = "Declared value 10.0001 has more than the 3 decimals of the token")]
+    fn test_declared_value_decimals() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("buy:{}:c1:1:10.0001", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128(10_000), msg);
+    }
+
+    #[test]
+    fn test_earnings_ledger() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let coto = |v: &str| Amount::parse_decimal(v).unwrap();
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .signer_account_id(accounts(2))
+            .predecessor_account_id(coto_id())
+            .build());
+        register(&mut context, &mut contract, "creator.testnet", "c1", 1);
+        contract.ft_on_transfer(
+            accounts(2),
+            U128(10_000),
+            "bid:creator.testnet:c1:1:2:10".into(),
+        );
+
+        // outbids every slot, so accounts(2) gets its 2 x 5 back
+        testing_env!(context.signer_account_id(accounts(3)).build());
+        contract.ft_on_transfer(
+            accounts(3),
+            U128(200_000),
+            "bid:creator.testnet:c1:1:20:200".into(),
+        );
+
+        let earnings = |contract: &Contract, account: &str| {
+            contract
+                .get_earnings(account.parse().unwrap())
+                .get(DEFAULT_COTO_ID)
+                .copied()
+                .unwrap_or_default()
+        };
+        assert_eq!(earnings(&contract, accounts(2).as_str()), coto("10"));
+        assert_eq!(earnings(&contract, "creator.testnet"), coto("180"));
+        assert_eq!(earnings(&contract, DEFAULT_TREASURY_ID), coto("20"));
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.claim_earnings(DEFAULT_COTO_ID.into());
+        assert!(contract.get_earnings(accounts(2)).is_empty());
+    }
+
+    #[test]
+    fn test_bid_events() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id("creator.testnet".parse().unwrap())
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 2, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let bid = |contract: &mut Contract, scout: AccountId, amount: u128, pt: i32| {
+            contract.ft_on_transfer(
+                scout,
+                U128(amount),
+                format!("bid:creator.testnet:c1:1:{}", pt),
+            );
+        };
+        bid(&mut contract, accounts(2), 10_000, 2);
+        bid(&mut contract, accounts(2), 4_000, 2);
+
+        // 8 a slot takes the slot held at 4 but not the one at 10, so 8 is unused
+        testing_env!(context.block_timestamp(1_650_000_000_000_000_000).build());
+        let seen = near_sdk::test_utils::get_logs().len();
+        bid(&mut contract, accounts(3), 16_000, 4);
+        let events: Vec<serde_json::Value> = near_sdk::test_utils::get_logs()[seen..]
+            .iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .map(|json| serde_json::from_str(json).unwrap())
+            .collect();
+        let names: Vec<&str> = events
+            .iter()
+            .map(|e| e["event"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["outbid", "payout", "payout", "content_bid", "refund"]
+        );
+        for event in &events {
+            assert_eq!(event["standard"], EVENT_STANDARD);
+            assert_eq!(event["version"], EVENT_VERSION);
+            assert_eq!(event["data"]["timestamp"], "1650000000000000000");
+        }
+        let coto = |v: &str| serde_json::json!(Amount::parse_decimal(v).unwrap());
+        assert_eq!(events[0]["data"]["previous_owner"], accounts(2).as_str());
+        assert_eq!(events[0]["data"]["new_owner"], accounts(3).as_str());
+        assert_eq!(events[0]["data"]["refund"], coto("4"));
+        assert_eq!(events[1]["data"]["reason"], "bid_creator");
+        assert_eq!(events[2]["data"]["reason"], "bid_treasury");
+        assert_eq!(events[3]["data"]["bids"].as_array().unwrap().len(), 3);
+        assert_eq!(events[4]["data"]["account_id"], accounts(3).as_str());
+        assert_eq!(events[4]["data"]["amount"], coto("8"));
+        assert_eq!(events[4]["data"]["reason"], "unused_bid");
+    }
+
+    #[test]
+    fn test_bid_remainder_event() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        testing_env!(context
+            .predecessor_account_id("creator.testnet".parse().unwrap())
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+
+        // 10.001 over 3 slots leaves 2 yocto, below the smallest COTO unit
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let seen = near_sdk::test_utils::get_logs().len();
+        let returned = contract.ft_on_transfer(
+            accounts(2),
+            U128(10_001),
+            "bid:creator.testnet:c1:1:6".into(),
+        );
+        assert!(matches!(returned, PromiseOrValue::Value(U128(0))));
+        let events: Vec<serde_json::Value> = near_sdk::test_utils::get_logs()[seen..]
+            .iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .map(|json| serde_json::from_str(json).unwrap())
+            .collect();
+        let last = events.last().unwrap();
+        assert_eq!(last["event"], "payout");
+        assert_eq!(last["data"]["reason"], "bid_remainder");
+        assert_eq!(last["data"]["account_id"], accounts(2).as_str());
+        assert_eq!(last["data"]["amount"], "2");
+        assert!(events.iter().all(|e| e["event"] != "refund"));
+        assert_eq!(
+            contract.get_earnings(accounts(2))[DEFAULT_COTO_ID],
+            Amount::from_yocto(2)
+        );
+    }
+
+    #[test]
+    fn test_failed_claim_is_recredited() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let amount = Amount::parse_decimal("1.5").unwrap();
+        contract.credit_earnings(accounts(2).as_str(), NEAR_TOKEN, amount);
+
+        contract.claim_earnings(NEAR_TOKEN.into());
+        assert!(contract.get_earnings(accounts(2)).is_empty());
+
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        assert!(!contract.resolve_transfer_funds(
+            accounts(2).to_string(),
+            NEAR_TOKEN.into(),
+            amount
+        ));
+        assert_eq!(contract.get_earnings(accounts(2))[NEAR_TOKEN], amount);
+    }
+
+    #[test]
+    fn test_bidding_window_and_sniping() {
+        const MINUTE: u64 = NS_PER_MINUTE;
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_timestamp(100 * MINUTE).build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        contract.set_bidding_window("c1".into(), 1, None, Some(U64(160 * MINUTE)), Some(5));
+
+        // 3 minutes before the end -> end moves to now + 5 minutes
+        testing_env!(context
+            .block_timestamp(157 * MINUTE)
+            .predecessor_account_id(coto_id())
+            .build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128(10_000), msg);
+        let content = contract.get_bidding_state("c1".into(), 1, accounts(1).to_string());
+        assert_eq!(content.biddingEnd, Some(U64(162 * MINUTE)));
+
+        testing_env!(context.block_timestamp(162 * MINUTE).build());
+        contract.settle_content("c1".into(), accounts(1).to_string(), 1);
+        let content = contract.get_bidding_state("c1".into(), 1, accounts(1).to_string());
+        assert!(content.settled);
+        assert_eq!(content.status, ContentStatus::Closed);
+    }
+
+    #[test]
+    fn test_bidding_window_after_bids() {
+        const MINUTE: u64 = NS_PER_MINUTE;
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_timestamp(100 * MINUTE).build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        contract.set_bidding_window("c1".into(), 1, None, Some(U64(160 * MINUTE)), Some(5));
+
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128(10_000), msg);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        let content =
+            contract.set_bidding_window("c1".into(), 1, None, Some(U64(200 * MINUTE)), Some(5));
+        assert_eq!(content.biddingEnd, Some(U64(200 * MINUTE)));
+        let content =
+            contract.set_bidding_window("c1".into(), 1, None, Some(U64(220 * MINUTE)), None);
+        assert_eq!(content.biddingEnd, Some(U64(220 * MINUTE)));
+        assert_eq!(content.snipeMinutes, 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "There are bids already, the bidding end can only be extended")]
+    fn test_bidding_window_not_shortened() {
+        const MINUTE: u64 = NS_PER_MINUTE;
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_timestamp(100 * MINUTE).build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        contract.set_bidding_window("c1".into(), 1, None, Some(U64(160 * MINUTE)), Some(5));
+
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128(10_000), msg);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_bidding_window("c1".into(), 1, None, Some(U64(120 * MINUTE)), Some(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Bidding has started, its start can not be changed")]
+    fn test_bidding_start_passed() {
+        const MINUTE: u64 = NS_PER_MINUTE;
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_timestamp(100 * MINUTE).build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        let (start, end) = (Some(U64(110 * MINUTE)), Some(U64(160 * MINUTE)));
+        contract.set_bidding_window("c1".into(), 1, start, end, None);
+
+        testing_env!(context.block_timestamp(120 * MINUTE).build());
+        contract.set_bidding_window("c1".into(), 1, Some(U64(130 * MINUTE)), end, None);
+    }
+
+    #[test]
+    fn test_content_status() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let creatorId = accounts(1).to_string();
+        let content = paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        assert_eq!(content.status, ContentStatus::Open);
+
+        let status = |contract: &mut Contract, status| {
+            contract
+                .set_content_status("c1".into(), accounts(1).to_string(), 1, status)
+                .status
+        };
+        assert_eq!(
+            status(&mut contract, ContentStatus::Draft),
+            ContentStatus::Draft
+        );
+        assert_eq!(
+            status(&mut contract, ContentStatus::Open),
+            ContentStatus::Open
+        );
+        assert_eq!(
+            status(&mut contract, ContentStatus::Closed),
+            ContentStatus::Closed
+        );
+
+        // closed content can still be licensed
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        contract.ft_on_transfer(accounts(3), U128(10_000), format!("buy:{}:c1:1", creatorId));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        assert_eq!(
+            status(&mut contract, ContentStatus::Delisted),
+            ContentStatus::Delisted
+        );
+        let hidden = contract.get_bidding_state("c1".into(), 1, creatorId.clone());
+        assert!(hidden.creatorId.is_empty());
+        let delisted = contract.dash_get_contents(None, None, Some(ContentStatus::Delisted));
+        assert_eq!(delisted.len(), 1);
+
+        // only an admin lists it again
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        assert_eq!(
+            status(&mut contract, ContentStatus::Open),
+            ContentStatus::Open
+        );
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        assert_eq!(
+            status(&mut contract, ContentStatus::Closed),
+            ContentStatus::Closed
+        );
+        assert_eq!(
+            status(&mut contract, ContentStatus::Archived),
+            ContentStatus::Archived
+        );
+        assert!(contract
+            .dash_get_contents(None, None, Some(ContentStatus::Open))
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Content is Closed, not open for bids")]
+    fn test_bid_on_closed_content() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        contract.set_content_status(
+            "c1".into(),
+            accounts(1).to_string(),
+            1,
+            ContentStatus::Closed,
+        );
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        contract.ft_on_transfer(accounts(2), U128(10_000), msg);
+    }
+
+    #[test]
+    #[should_panic(expected = "Content is Delisted, not open for licences")]
+    fn test_licence_on_delisted_content() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_content_status(
+            "c1".into(),
+            accounts(1).to_string(),
+            1,
+            ContentStatus::Delisted,
+        );
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        contract.ft_on_transfer(
+            accounts(3),
+            U128(10_000),
+            format!("buy:{}:c1:1", accounts(1)),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only an admin can list delisted content again")]
+    fn test_delisted_content_owner_only() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        contract.set_content_status(
+            "c1".into(),
+            accounts(1).to_string(),
+            1,
+            ContentStatus::Delisted,
+        );
+        contract.set_content_status("c1".into(), accounts(1).to_string(), 1, ContentStatus::Open);
+    }
+
+    #[test]
+    #[should_panic(expected = "Content can not go from Archived to Open")]
+    fn test_archived_content_is_final() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        for status in [
+            ContentStatus::Closed,
+            ContentStatus::Archived,
+            ContentStatus::Open,
+        ] {
+            contract.set_content_status("c1".into(), accounts(1).to_string(), 1, status);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Bidding closed at")]
+    fn test_bid_after_window() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.block_timestamp(100).build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        contract.set_bidding_window("c1".into(), 1, None, Some(U64(200)), None);
+
+        testing_env!(context
+            .block_timestamp(200)
+            .predecessor_account_id(coto_id())
+            .build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        contract.ft_on_transfer(accounts(1), U128(10_000), msg);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs at least 10 (min increment 1)")]
+    fn test_min_outbid_increment() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let coto = |v: &str| Amount::parse_decimal(v).unwrap();
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 1, 2, None)
+        });
+        // 10% of 8 is 0.8, so the absolute 1 applies
+        contract.set_min_increment("c1".into(), 1, coto("1"), 10);
+
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let bid = |contract: &mut Contract, val: &str| {
+            let msg = format!("bid:{}:c1:1:2:{}", accounts(1), val);
+            contract.ft_on_transfer(accounts(1), coto(val).to_units(COTO_DECIMALS), msg);
+        };
+        bid(&mut contract, "8");
+        bid(&mut contract, "9");
+        let content = contract.get_bidding_state("c1".into(), 1, accounts(1).to_string());
+        assert_eq!(content.bidvalArr, vec![coto("9")]);
+        bid(&mut contract, "9.5");
+    }
+
+    #[test]
+    #[should_panic(expected = "The minimum increment must be positive")]
+    fn test_min_increment_positive() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 1, 2, None)
+        });
+        contract.set_min_increment("c1".into(), 1, Amount::ZERO, 0);
+    }
+
+    #[test]
+    fn test_quote_bid_matches_bid() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let coto = |v: &str| Amount::parse_decimal(v).unwrap();
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .signer_account_id(accounts(2))
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 3, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        contract.ft_on_transfer(
+            accounts(2),
+            U128(10_000),
+            format!("bid:{}:c1:1:2:10", accounts(1)),
+        );
+
+        // 3 x 11 per slot: the two empty slots and the one held by accounts(2) at 10 (+0.5 min)
+        let quote = contract.quote_bid("c1".into(), accounts(1).to_string(), 1, coto("33"), 6);
+        assert_eq!(quote.percentWon, 6);
+        assert_eq!(quote.refund, Amount::ZERO);
+        assert_eq!(quote.slots[2].previousOwner, Some(accounts(2).to_string()));
+        assert_eq!(quote.creatorShare + quote.treasuryShare, coto("23"));
+        assert_eq!(quote.minNextBid, coto("0.01"));
+
+        testing_env!(context.signer_account_id(accounts(3)).build());
+        let msg = format!("bid:{}:c1:1:6:33", accounts(1));
+        contract.ft_on_transfer(accounts(3), U128(33_000), msg);
+        let earnings = contract.get_earnings(accounts(1));
+        assert_eq!(earnings[DEFAULT_COTO_ID], coto("9") + quote.creatorShare);
+        assert_eq!(
+            contract.get_earnings(accounts(2))[DEFAULT_COTO_ID],
+            coto("10")
+        );
+    }
+
+    #[test]
+    fn test_quote_licence() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let coto = |v: &str| Amount::parse_decimal(v).unwrap();
+
+        register(&mut context, &mut contract, accounts(1).as_str(), "new", 1);
+        let quote = contract.quote_licence("new".into(), accounts(1).to_string(), 1, None);
+        assert_eq!(quote.minPrice, DEFAULT_MIN_LICENCE_PRICE);
+        assert_eq!(quote.token, None);
+        assert_eq!(quote.payouts.len(), 1);
+        assert_eq!(
+            quote.treasuryFee + quote.payouts[0].amount,
+            DEFAULT_MIN_LICENCE_PRICE
+        );
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .signer_account_id(accounts(2))
+            .build());
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        contract.ft_on_transfer(
+            accounts(2),
+            U128(10_000),
+            format!("bid:{}:c1:1:4:10", accounts(1)),
+        );
+
+        let quote = contract.quote_licence("c1".into(), accounts(1).to_string(), 1, None);
+        assert_eq!(quote.minPrice, coto("10"));
+        assert_eq!(quote.token, Some(DEFAULT_COTO_ID.to_string()));
+        assert_eq!(quote.treasuryFee, coto("1"));
+        assert_eq!(quote.payouts[0].amount, coto("8.64"));
+        assert_eq!(quote.payouts[1].ownerId, accounts(2).to_string());
+        assert_eq!(quote.payouts[1].amount, coto("0.36"));
+
+        testing_env!(context.signer_account_id(accounts(3)).build());
+        contract.ft_on_transfer(
+            accounts(3),
+            U128(10_000),
+            format!("buy:{}:c1:1:10", accounts(1)),
+        );
+        assert_eq!(
+            contract.get_earnings(accounts(2))[DEFAULT_COTO_ID],
+            quote.payouts[1].amount
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Licences of c1 are paid in ft1.setalosas.testnet, not near")]
+    fn test_licence_in_bid_token() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        contract.ft_on_transfer(
+            accounts(2),
+            U128(10_000),
+            format!("bid:{}:c1:1:4:10", accounts(1)),
+        );
+
+        //.rem  10 NEAR is not 10 COTO
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(10 * E24)
+            .build());
+        contract.buy_licence(
+            "c1".into(),
+            accounts(1).to_string(),
+            1,
+            accounts(3).to_string(),
+            Amount::parse_decimal("10").unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_migrate_from_v0() {
+        migrate_from_v0(1);
+    }
+
+    //.rem  a page past the contents must not skip the first licences
+    #[test]
+    fn test_migrate_from_v0_large_pages() {
+        migrate_from_v0(10);
+    }
+
+    fn migrate_from_v0(limit: u64) {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contentMetadata = NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "content".into(),
+            symbol: "COW".into(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        };
+        let metadata = || LazyOption::new(StorageKey::Metadata, Some(&contentMetadata));
+        let tokens = || {
+            NonFungibleToken::new(
+                StorageKey::NonFungibleToken,
+                accounts(0),
+                Some(StorageKey::TokenMetadata),
+                Some(StorageKey::Enumeration),
+                Some(StorageKey::Approval),
+            )
+        };
+        let contentV0 = |contentId: &str, tokenId: usize, topBid: f32| {
+            let mut bidvalArr = [0_f32; migration::BIDPT_V0];
+            bidvalArr[migration::BIDPT_V0 - 1] = topBid;
+            let mut tokensArr = [0; migration::BIDPT_V0];
+            for (i, slotTokenId) in tokensArr.iter_mut().enumerate() {
+                *slotTokenId = tokenId + i + 1;
+            }
+            migration::ContentRecV0 {
+                creatorId: accounts(1).to_string(),
+                contentId: contentId.into(),
+                timestamp: 1,
+                bidvalArr,
+                tokensArr,
+                tokenId,
+            }
+        };
+
+        // V0: both collections on the same prefixes
+        let (mut contentTokens, mut licenceTokens) = (tokens(), tokens());
+        let mut contents = HashMap::new();
+        for (contentId, tokenId, topBid) in [("a", 1100, 22.5), ("b", 1200, 0.1)] {
+            for i in 0..=migration::BIDPT_V0 {
+                let owner = if i == migration::BIDPT_V0 {
+                    accounts(2)
+                } else {
+                    accounts(1)
+                };
+                contentTokens.internal_mint_with_refund(
+                    tokenId2Str(tokenId + i),
+                    owner,
+                    Some(sample_token_metadata()),
+                    None,
+                );
+            }
+            let key = format!("{}:{}:1", contentId, accounts(1));
+            contents.insert(key, contentV0(contentId, tokenId, topBid));
+        }
+        licenceTokens.internal_mint_with_refund(
+            tokenId2Str(1_000_001),
+            accounts(2),
+            Some(sample_token_metadata()),
+            None,
+        );
+        env::state_write(&migration::ContractV0 {
+            tokens: contentTokens,
+            licenceTokens,
+            lastTokenId: 1200,
+            lastContentTokenId: 1_000_001,
+            metadata: metadata(),
+            licenceMetadata: metadata(),
+            cnt: 7,
+            contents,
+            emptyContentRec: contentV0("", 0, 0.0),
+            lock: 0,
+        });
+
+        testing_env!(context.build());
+        let mut contract = Contract::migrate();
+        let mut next = Some(U64(0));
+        while let Some(from_index) = next {
+            testing_env!(context.build());
+            next = contract.migrate_nfts(from_index, limit);
+        }
+        // repeating is harmless
+        contract.migrate_nfts(U64(0), 10);
+        assert_eq!(migration::state_version(), migration::STATE_VERSION);
+        assert_eq!(contract.cnt, 7);
+        assert_eq!(contract.dash_get_contents_count(), 2);
+        assert_eq!(
+            contract
+                .dash_get_contents(Some(U64(1)), Some(5), None)
+                .len(),
+            1
+        );
+        let content = contract.get_bidding_state("a".into(), 1, accounts(1).to_string());
+        assert_eq!(content.creatorPt, 80);
+        assert_eq!(
+            content.bidvalArr[19],
+            Amount::parse_decimal("22.5").unwrap()
+        );
+        let content = contract.get_bidding_state("b".into(), 1, accounts(1).to_string());
+        assert_eq!(content.bidvalArr[19], Amount::parse_decimal("0.1").unwrap());
+
+        assert_eq!(contract.nft_total_supply(), U128(42));
+        assert!(contract.nft_token(tokenId2Str(1_000_001)).is_none());
+        let owned = contract.nft_tokens_for_owner(accounts(2), None, None);
+        assert_eq!(owned.len(), 2);
+        assert_eq!(owned[0].metadata, Some(sample_token_metadata()));
+        let licence = contract
+            .licenceTokens
+            .owner_by_id
+            .get(&tokenId2Str(1_000_001));
+        assert_eq!(licence, Some(accounts(2)));
+        let licences = contract.licence_nft_tokens_for_owner(accounts(2), None, None);
+        assert_eq!(licences.len(), 1);
+        assert_eq!(contract.licence_nft_metadata().symbol, "CLI");
+        assert_eq!(contract.nft_metadata().symbol, "COW");
+    }
+
+    #[test]
+    #[should_panic(expected = "The limit must be positive")]
+    fn test_migrate_nfts_limit() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.migrate_nfts(U64(0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can migrate")]
+    fn test_migrate_owner_only() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let tokens = || Contract::new_content_collection(accounts(0));
+        env::state_write(&migration::ContractV0 {
+            tokens: tokens(),
+            licenceTokens: tokens(),
+            lastTokenId: 1000,
+            lastContentTokenId: LICENCE_TOKEN_ID_BASE,
+            metadata: LazyOption::new(StorageKey::Metadata, None),
+            licenceMetadata: LazyOption::new(StorageKey::Metadata, None),
+            cnt: 0,
+            contents: HashMap::new(),
+            emptyContentRec: migration::ContentRecV0 {
+                creatorId: String::new(),
+                contentId: String::new(),
+                timestamp: 0,
+                bidvalArr: [0.0; migration::BIDPT_V0],
+                tokensArr: [0; migration::BIDPT_V0],
+                tokenId: 0,
+            },
+            lock: 0,
+        });
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        Contract::migrate();
+    }
+
+    #[test]
+    fn test_migrate_current_state() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.cnt = 3;
+        env::state_write(&contract);
+        assert_eq!(Contract::migrate().cnt, 3);
+    }
+
+    #[test]
+    fn test_unaccepted_token_is_returned() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        let res = contract.ft_on_transfer(
+            accounts(2),
+            U128(10_000),
+            "bid:creator.testnet:c1:1:2:10".into(),
+        );
+        match res {
+            PromiseOrValue::Value(value) => assert_eq!(value, U128(10_000)),
+            PromiseOrValue::Promise(_) => panic!("Expected PromiseOrValue::Value"),
+        }
+        assert!(contract.contents.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Bids on c1 are paid in ft1.setalosas.testnet, not charlie")]
+    fn test_bid_token_per_content() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let config = Config {
+            acceptedTokens: vec![
+                AcceptedToken {
+                    tokenId: coto_id(),
+                    decimals: COTO_DECIMALS,
+                },
+                AcceptedToken {
+                    tokenId: accounts(2),
+                    decimals: COTO_DECIMALS,
+                },
+            ],
+            ..contract.get_config()
+        };
+        contract.set_config(config);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .signer_account_id(accounts(1))
+            .predecessor_account_id(coto_id())
+            .build());
+        register(&mut context, &mut contract, "creator.testnet", "c1", 1);
+        register(&mut context, &mut contract, "creator.testnet", "c2", 1);
+        contract.ft_on_transfer(
+            accounts(1),
+            U128(10_000),
+            "bid:creator.testnet:c1:1:2:10".into(),
+        );
+        let content = contract.get_bidding_state("c1".into(), 1, "creator.testnet".into());
+        assert_eq!(content.bidToken, Some(DEFAULT_COTO_ID.to_string()));
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ft_on_transfer(
+            accounts(1),
+            U128(10_000),
+            "bid:creator.testnet:c2:1:2:10".into(),
+        );
+        assert_eq!(
+            contract.get_earnings("creator.testnet".parse().unwrap())[accounts(2).as_str()],
+            Amount::parse_decimal("9").unwrap()
+        );
+        contract.ft_on_transfer(
+            accounts(1),
+            U128(20_000),
+            "bid:creator.testnet:c1:1:2:20".into(),
+        );
+    }
+
+    #[test]
+    fn test_token_decimals() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let usdc = AcceptedToken {
+            tokenId: accounts(2),
+            decimals: 6,
+        };
+        contract.set_config(Config {
+            acceptedTokens: vec![usdc.clone()],
+            ..contract.get_config()
+        });
+
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        register(&mut context, &mut contract, "creator.testnet", "c1", 1);
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.ft_on_transfer(
+            accounts(1),
+            U128(10_000_000),
+            "bid:creator.testnet:c1:1:2:10".into(),
+        );
+        let creatorId: AccountId = "creator.testnet".parse().unwrap();
+        assert_eq!(
+            contract.get_earnings(creatorId.clone())[accounts(2).as_str()],
+            Amount::parse_decimal("9").unwrap()
+        );
+
+        //.rem  no longer accepted, but the earnings stay claimable with its decimals
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.set_config(Config::default());
+        testing_env!(context.predecessor_account_id(creatorId.clone()).build());
+        contract.claim_earnings(accounts(2).to_string());
+        assert!(contract.get_earnings(creatorId).is_empty());
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts[0].receiver_id, accounts(2));
+        match &receipts[0].actions[0] {
+            near_sdk::mock::VmAction::FunctionCall {
+                function_name,
+                args,
+                gas,
+                deposit,
+            } => {
+                assert_eq!(function_name, "ft_transfer");
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["receiver_id"], "creator.testnet");
+                assert_eq!(args["amount"], "9000000");
+                assert_eq!((*gas, *deposit), (FT_TRANSFER_GAS, 1));
+            }
+            action => panic!("unexpected {:?}", action),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "charlie can have at most 24 decimals")]
+    fn test_token_decimals_limit() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.set_config(Config {
+            acceptedTokens: vec![AcceptedToken {
+                tokenId: accounts(2),
+                decimals: 25,
+            }],
+            ..contract.get_config()
+        });
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Admin);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.grant_role(accounts(2), Role::Operator);
+        assert!(Role::Operator < Role::Guardian && Role::Guardian < Role::Admin);
+        assert!(Role::Admin < Role::Owner);
+        assert_eq!(
+            contract.get_roles(),
+            vec![(accounts(1), Role::Admin), (accounts(2), Role::Operator)]
+        );
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.revoke_role(accounts(2));
+        assert_eq!(contract.get_role(accounts(2)), None);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.revoke_role(accounts(1));
+        assert!(contract.get_roles().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can grant the Admin role")]
+    fn test_grant_admin_owner_only() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Admin);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.grant_role(accounts(2), Role::Admin);
+    }
+
+    #[cfg(feature = "dev")]
+    #[test]
+    fn test_counter() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Admin);
+        contract.grant_role(accounts(2), Role::Operator);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.modcont();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.inc_cnt();
+        contract.add_cnt(2);
+        assert_eq!(contract.get_contract_cnt(), 3);
+    }
+
+    #[cfg(feature = "dev")]
+    #[test]
+    #[should_panic(expected = "Only an operator can change the counter")]
+    fn test_counter_operator_only() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.inc_cnt();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only an admin can mint")]
+    fn test_nft_mint_admin_only() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(1), Role::Operator);
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(MINT_STORAGE_COST)
+            .build());
+        contract.nft_mint("0".to_string(), accounts(1), sample_token_metadata());
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(4), Role::Guardian);
+        register(&mut context, &mut contract, accounts(1).as_str(), "c1", 1);
+
+        testing_env!(context.predecessor_account_id(accounts(4)).build());
+        let paused = contract.pause(Some(vec![PauseScope::Bids]));
+        assert_eq!(paused, vec![PauseScope::Bids]);
+
+        // the paused bid comes back whole, licences are still sold
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        match contract.ft_on_transfer(accounts(2), U128(10_000), msg.clone()) {
+            PromiseOrValue::Value(value) => assert_eq!(value, U128(10_000)),
+            PromiseOrValue::Promise(_) => panic!("Expected PromiseOrValue::Value"),
+        }
+        let content = contract.get_bidding_state("c1".into(), 1, accounts(1).to_string());
+        assert!(content.bidvalArr.iter().all(|val| val.is_zero()));
+        contract.ft_on_transfer(
+            accounts(3),
+            U128(10_000),
+            format!("buy:{}:c1:1", accounts(1)),
+        );
+        assert!(!contract.get_earnings(accounts(1)).is_empty());
+
+        testing_env!(context.predecessor_account_id(accounts(4)).build());
+        assert_eq!(contract.pause(None).len(), 3);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let paused = contract.unpause(Some(vec![PauseScope::Bids, PauseScope::Licences]));
+        assert_eq!(paused, vec![PauseScope::Transfers]);
+
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        match contract.ft_on_transfer(accounts(2), U128(10_000), msg) {
+            PromiseOrValue::Value(value) => assert_eq!(value, U128(0)),
+            PromiseOrValue::Promise(_) => panic!("Expected PromiseOrValue::Value"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Only an admin can unpause")]
+    fn test_unpause_admin_only() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.grant_role(accounts(4), Role::Guardian);
+        testing_env!(context.predecessor_account_id(accounts(4)).build());
+        contract.pause(None);
+        contract.unpause(None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers are paused")]
+    fn test_paused_nft_transfer() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context
+            .attached_deposit(MINT_STORAGE_COST)
+            .predecessor_account_id(accounts(0))
+            .build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        contract.nft_mint("0".to_string(), accounts(0), sample_token_metadata());
+        contract.pause(Some(vec![PauseScope::Transfers]));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_transfer(accounts(1), "0".to_string(), None, None);
+    }
+
+    // creator of c1 starts an nft_transfer_call of the creator token
+    fn settlement_setup() -> (VMContextBuilder, Contract, TokenId) {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let content = paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 10, 2, None)
+        });
+        let tokenId = tokenId2Str(content.tokenId);
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_transfer_call(accounts(3), tokenId.clone(), None, None, "".into());
+        testing_env!(context.attached_deposit(0).build());
+        (context, contract, tokenId)
+    }
+
+    #[test]
+    fn test_settlement() {
+        let (mut context, mut contract, tokenId) = settlement_setup();
+        let key = format!("c1:{}:1", accounts(1));
+        let pending = contract.get_pending_settlements(None, None);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, key);
+        assert_eq!(pending[0].1.tokenIds, vec![tokenId.clone()]);
+
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"false".to_vec())],
+        );
+        assert!(contract.nft_resolve_transfer(accounts(1), accounts(3), tokenId.clone(), None));
+        assert!(contract.get_pending_settlements(None, None).is_empty());
+        assert_eq!(contract.nft_token(tokenId).unwrap().owner_id, accounts(3));
+    }
+
+    #[test]
+    fn test_bid_during_settlement() {
+        let (mut context, mut contract, creatorTokenId) = settlement_setup();
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        contract.ft_on_transfer(accounts(2), U128(10_000), msg);
+        let msg = format!("buy:{}:c1:1", accounts(1));
+        contract.ft_on_transfer(accounts(4), U128(10_000), msg);
+        assert_eq!(contract.get_pending_settlements(None, None).len(), 1);
+        assert_eq!(
+            contract.nft_token(creatorTokenId).unwrap().owner_id,
+            accounts(3)
+        );
+    }
+
+    // an NFT outbid in flight goes to the bidder, its refund waits for the transfer to resolve
+    fn outbid_in_transfer(returned: bool) -> (Contract, TokenId) {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 1, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        contract.ft_on_transfer(accounts(2), U128(10_000), msg);
+        let content = contract.get_bidding_state("c1".into(), 1, accounts(1).to_string());
+        let slotTokenId = tokenId2Str(content.tokensArr[0]);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.nft_transfer_call(accounts(3), slotTokenId.clone(), None, None, "".into());
+        testing_env!(context
+            .predecessor_account_id(coto_id())
+            .attached_deposit(0)
+            .build());
+        let msg = format!("bid:{}:c1:1:2:20", accounts(1));
+        contract.ft_on_transfer(accounts(4), U128(20_000), msg);
+        assert!(contract.get_earnings(accounts(3)).is_empty());
+        let pending = contract.get_pending_settlements(None, None);
+        assert_eq!(pending[0].1.held.len(), 1);
+
+        let result = if returned { "true" } else { "false" };
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(result.as_bytes().to_vec())],
+        );
+        contract.nft_resolve_transfer(accounts(2), accounts(3), slotTokenId.clone(), None);
+        assert!(contract.get_pending_settlements(None, None).is_empty());
+        assert_eq!(
+            contract.nft_token(slotTokenId.clone()).unwrap().owner_id,
+            accounts(4)
+        );
+        (contract, slotTokenId)
+    }
+
+    #[test]
+    fn test_outbid_in_transfer_kept() {
+        let (contract, _) = outbid_in_transfer(false);
+        let refund = Amount::parse_decimal("10").unwrap();
+        assert_eq!(contract.get_earnings(accounts(3))[DEFAULT_COTO_ID], refund);
+        assert!(contract.get_earnings(accounts(2)).is_empty());
+    }
+
+    #[test]
+    fn test_outbid_in_transfer_returned() {
+        let (contract, _) = outbid_in_transfer(true);
+        let refund = Amount::parse_decimal("10").unwrap();
+        assert_eq!(contract.get_earnings(accounts(2))[DEFAULT_COTO_ID], refund);
+        assert!(contract.get_earnings(accounts(3)).is_empty());
+    }
+
+    #[test]
+    fn test_outbid_in_nested_transfer() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        paying_storage(&mut context, || {
+            contract.register_content("c1".into(), 1, 1, 2, None)
+        });
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        let msg = format!("bid:{}:c1:1:2:10", accounts(1));
+        contract.ft_on_transfer(accounts(2), U128(10_000), msg);
+        let content = contract.get_bidding_state("c1".into(), 1, accounts(1).to_string());
+        let slotTokenId = tokenId2Str(content.tokensArr[0]);
+
+        // 2 -> 3, and 3 passes it on to 5 from its nft_on_transfer
+        for (from, to) in [(2, 3), (3, 5)] {
+            testing_env!(context
+                .predecessor_account_id(accounts(from))
+                .attached_deposit(1)
+                .build());
+            contract.nft_transfer_call(accounts(to), slotTokenId.clone(), None, None, "".into());
+        }
+        testing_env!(context
+            .predecessor_account_id(coto_id())
+            .attached_deposit(0)
+            .build());
+        let msg = format!("bid:{}:c1:1:2:20", accounts(1));
+        contract.ft_on_transfer(accounts(4), U128(20_000), msg);
+
+        let resolve = |contract: &mut Contract, from: usize, to: usize| {
+            testing_env!(
+                get_context(accounts(0)).build(),
+                near_sdk::VMConfig::test(),
+                near_sdk::RuntimeFeesConfig::test(),
+                Default::default(),
+                vec![PromiseResult::Successful(b"true".to_vec())],
+            );
+            contract.nft_resolve_transfer(accounts(from), accounts(to), slotTokenId.clone(), None);
+        };
+        resolve(&mut contract, 3, 5);
+        let pending = contract.get_pending_settlements(None, None);
+        assert_eq!(pending[0].1.tokenIds, vec![slotTokenId.clone()]);
+        assert_eq!(pending[0].1.held[0].ownerId, accounts(3));
+        assert!(contract.get_earnings(accounts(3)).is_empty());
+
+        resolve(&mut contract, 2, 3);
+        assert!(contract.get_pending_settlements(None, None).is_empty());
+        let refund = Amount::parse_decimal("10").unwrap();
+        assert_eq!(contract.get_earnings(accounts(2))[DEFAULT_COTO_ID], refund);
+        assert!(contract.get_earnings(accounts(5)).is_empty());
+    }
+
+    #[test]
+    fn test_clear_settlement() {
+        let (mut context, mut contract, tokenId) = settlement_setup();
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        let settlement = contract.clear_settlement(format!("c1:{}:1", accounts(1)));
+        assert_eq!(settlement.tokenIds, vec![tokenId]);
+        assert!(contract.get_pending_settlements(None, None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only an admin can clear settlements")]
+    fn test_clear_settlement_admin_only() {
+        let (_context, mut contract, _) = settlement_setup();
+        contract.clear_settlement(format!("c1:{}:1", accounts(1)));
+    }
+
+    #[test]
+    fn test_config() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        let coto = |v: &str| Amount::parse_decimal(v).unwrap();
+
+        let config = Config {
+            treasuryId: accounts(4),
+            treasuryPt: 20,
+            minLicencePrice: coto("1"),
+            licencePricePt: 50,
+            ..contract.get_config()
+        };
+        assert_eq!(contract.set_config(config.clone()), config);
+
+        register(&mut context, &mut contract, accounts(1).as_str(), "new", 1);
+        let quote = contract.quote_licence("new".into(), accounts(1).to_string(), 1, None);
+        assert_eq!(quote.minPrice, coto("1"));
+        assert_eq!(quote.treasuryFee, coto("0.2"));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(MINT_STORAGE_COST)
+            .signer_account_id(accounts(2))
+            .build());
+        register(&mut context, &mut contract, accounts(1).as_str(), "c1", 1);
+        testing_env!(context.predecessor_account_id(coto_id()).build());
+        contract.ft_on_transfer(
+            accounts(2),
+            U128(10_000),
+            format!("bid:{}:c1:1:4:10", accounts(1)),
+        );
+        let quote = contract.quote_licence("c1".into(), accounts(1).to_string(), 1, None);
+        assert_eq!(quote.minPrice, coto("5"));
+        assert_eq!(quote.treasuryFee, coto("1"));
+        assert_eq!(
+            contract.get_earnings(accounts(4))[DEFAULT_COTO_ID],
+            coto("2")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can change the config")]
+    fn test_set_config_owner_only() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut contract = Contract::new_default_meta(accounts(0));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_config(Config::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can upgrade")]
+    fn test_upgrade_owner_only() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .prepaid_gas(Gas(300_000_000_000_000))
+            .build());
+        contract.upgrade(vec![0, 97, 115, 109].into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Attach at least 80 Tgas for upgrade and migrate")]
+    fn test_upgrade_gas() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = Contract::new_default_meta(accounts(0));
+
+        testing_env!(context.prepaid_gas(Gas(20_000_000_000_000)).build());
+        contract.upgrade(vec![0, 97, 115, 109].into());
     }
 }
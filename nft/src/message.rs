@@ -0,0 +1,316 @@
+//! Messages sent with `ft_transfer_call` to this contract and handled by `ft_on_transfer`.
+//!
+//! Two forms are accepted:
+//! - the legacy colon form, the closing `:` is optional and so is the value:
+//!   `bid:<creatorId>:<contentId>:<timestamp>:<pt>[:<value>]`
+//!   `buy:<creatorId>:<contentId>:<timestamp>[:<value>]`
+//!   The value is a decimal token amount like `22.5`. Ids containing `:` need the JSON form.
+//! - JSON with a version: `{"version":1,"op":"bid","creatorId":"..","contentId":"..",
+//!   "timestamp":123,"pt":10,"value":"22500000000000000000000000"}`
+//!   The value is optional here as well, in yocto units like every `Amount` in JSON.
+//!   An optional `beneficiaryId` bids or buys on behalf of another account, the slots or the
+//!   licence go to it. Without one they go to the `sender_id` of the transfer.
+//!
+//! The transferred amount is what is bid or paid, a value in the message only has to match it.
+//! It can have at most the decimals of the FT contract, `ft_on_transfer` rejects smaller units.
+//!
+//! `ContentApproval` is what a creator signs to let a platform register content for them.
+//! Nothing here depends on the contract environment, clients can build messages with `to_json`
+//! and the bytes to sign with `ContentApproval::to_signed_bytes`.
+
+use crate::amount::Amount;
+use crate::ContentMetadata;
+use near_sdk::{serde_json, AccountId};
+use serde::{Deserialize, Serialize};
+
+pub const MESSAGE_VERSION: u32 = 1;
+
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+pub enum TransferMessage {
+    Bid {
+        creatorId: String,
+        contentId: String,
+        timestamp: u64,
+        pt: i32, // size of the bid in percent of the content
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<Amount>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        beneficiaryId: Option<AccountId>,
+    },
+    Buy {
+        creatorId: String,
+        contentId: String,
+        timestamp: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<Amount>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        beneficiaryId: Option<AccountId>,
+    },
+}
+
+//.rem  passed to register_content_for, signed by the creator with the key of set_creator_key
+#[allow(non_snake_case)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentApproval {
+    pub contractId: AccountId, // an approval can only be used on this contract
+    pub creatorId: AccountId,
+    pub contentId: String,
+    pub timestamp: u64,
+    pub slotCount: usize,
+    pub slotPt: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ContentMetadata>,
+}
+
+impl ContentApproval {
+    /// The signed bytes: compact JSON with the fields in the order above.
+    pub fn to_signed_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("ContentApproval is always serializable")
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    #[serde(flatten)]
+    message: TransferMessage,
+}
+
+impl TransferMessage {
+    pub fn parse(msg: &str) -> Result<Self, String> {
+        let msg = msg.trim();
+        if msg.starts_with('{') {
+            Self::parse_json(msg)
+        } else {
+            Self::parse_legacy(msg)
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&Envelope {
+            version: MESSAGE_VERSION,
+            message: self.clone(),
+        })
+        .expect("TransferMessage is always serializable")
+    }
+
+    fn parse_json(msg: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(msg).map_err(|e| format!("invalid JSON message: {}", e))?;
+        match value.get("version").map(|version| version.as_u64()) {
+            None => return Err("JSON message has no version".into()),
+            Some(Some(version)) if version == MESSAGE_VERSION as u64 => {}
+            Some(version) => {
+                return Err(format!(
+                    "unsupported message version {}, expected {}",
+                    version.map_or_else(|| value["version"].to_string(), |v| v.to_string()),
+                    MESSAGE_VERSION
+                ))
+            }
+        }
+        let envelope: Envelope =
+            serde_json::from_value(value).map_err(|e| format!("invalid JSON message: {}", e))?;
+        envelope.message.validate()
+    }
+
+    fn parse_legacy(msg: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = msg.split(':').collect();
+        if parts.len() > 1 && parts.last() == Some(&"") {
+            parts.pop();
+        }
+        let op = parts[0];
+        let (usage, min, max) = match op {
+            "bid" => ("bid:creatorId:contentId:timestamp:pt[:value]", 5, 6),
+            "buy" => ("buy:creatorId:contentId:timestamp[:value]", 4, 5),
+            "" => return Err("empty message".into()),
+            _ => return Err(format!("unknown op '{}'", op)),
+        };
+        if parts.len() < min || parts.len() > max {
+            return Err(format!(
+                "expected {}, got {} parts{}",
+                usage,
+                parts.len(),
+                if parts.len() > max {
+                    " (ids containing ':' need the JSON form)"
+                } else {
+                    ""
+                }
+            ));
+        }
+        let creatorId = parts[1].to_string();
+        let contentId = parts[2].to_string();
+        let timestamp = parse_number::<u64>(parts[3], "timestamp")?;
+        let value = |ix: usize| -> Result<Option<Amount>, String> {
+            parts
+                .get(ix)
+                .map(|part| Amount::parse_decimal(part).map_err(|e| format!("value: {}", e)))
+                .transpose()
+        };
+        let message = match op {
+            "bid" => TransferMessage::Bid {
+                creatorId,
+                contentId,
+                timestamp,
+                pt: parse_number::<i32>(parts[4], "pt")?,
+                value: value(5)?,
+                beneficiaryId: None,
+            },
+            _ => TransferMessage::Buy {
+                creatorId,
+                contentId,
+                timestamp,
+                value: value(4)?,
+                beneficiaryId: None,
+            },
+        };
+        message.validate()
+    }
+
+    pub fn beneficiary(&self) -> Option<&AccountId> {
+        match self {
+            TransferMessage::Bid { beneficiaryId, .. }
+            | TransferMessage::Buy { beneficiaryId, .. } => beneficiaryId.as_ref(),
+        }
+    }
+
+    fn validate(self) -> Result<Self, String> {
+        let (creatorId, contentId) = match &self {
+            TransferMessage::Bid {
+                creatorId,
+                contentId,
+                ..
+            }
+            | TransferMessage::Buy {
+                creatorId,
+                contentId,
+                ..
+            } => (creatorId, contentId),
+        };
+        if creatorId.is_empty() {
+            return Err("empty creatorId".into());
+        }
+        if contentId.is_empty() {
+            return Err("empty contentId".into());
+        }
+        Ok(self)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(part: &str, name: &str) -> Result<T, String> {
+    part.parse::<T>()
+        .map_err(|_| format!("invalid {} '{}'", name, part))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    fn bid(value: Option<&str>) -> TransferMessage {
+        TransferMessage::Bid {
+            creatorId: "creator.testnet".into(),
+            contentId: "85d491b3".into(),
+            timestamp: 123367777,
+            pt: 10,
+            value: value.map(|v| Amount::parse_decimal(v).unwrap()),
+            beneficiaryId: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_legacy() {
+        let msg = "bid:creator.testnet:85d491b3:123367777:10:22.5:";
+        assert_eq!(TransferMessage::parse(msg).unwrap(), bid(Some("22.5")));
+        let msg = "bid:creator.testnet:85d491b3:123367777:10";
+        assert_eq!(TransferMessage::parse(msg).unwrap(), bid(None));
+        let msg = "buy:creator.testnet:85d491b3:123367777:";
+        assert_eq!(
+            TransferMessage::parse(msg).unwrap(),
+            TransferMessage::Buy {
+                creatorId: "creator.testnet".into(),
+                contentId: "85d491b3".into(),
+                timestamp: 123367777,
+                value: None,
+                beneficiaryId: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_legacy_errors() {
+        let err = |msg: &str| TransferMessage::parse(msg).unwrap_err();
+        assert_eq!(err(""), "empty message");
+        assert_eq!(err("sell:a:b:1"), "unknown op 'sell'");
+        assert_eq!(
+            err("bid:a:b:1"),
+            "expected bid:creatorId:contentId:timestamp:pt[:value], got 4 parts"
+        );
+        assert_eq!(
+            err("bid:a:b:c:1:10:5"),
+            "expected bid:creatorId:contentId:timestamp:pt[:value], got 7 parts \
+             (ids containing ':' need the JSON form)"
+        );
+        assert_eq!(err("bid:a:b:x:10"), "invalid timestamp 'x'");
+        assert_eq!(err("bid:a:b:1:ten"), "invalid pt 'ten'");
+        assert_eq!(err("buy:a:b:1:1e5"), "value: invalid amount '1e5'");
+        assert_eq!(err("buy:a::1"), "empty contentId");
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let message = TransferMessage::Bid {
+            creatorId: "creator.testnet".into(),
+            contentId: "a:b".into(),
+            timestamp: 1,
+            pt: 10,
+            value: Some(Amount::parse_decimal("22.5").unwrap()),
+            beneficiaryId: Some("friend.testnet".parse().unwrap()),
+        };
+        let json = message.to_json();
+        assert!(json.contains("\"version\":1"));
+        assert!(json.contains("\"op\":\"bid\""));
+        assert!(json.contains("\"beneficiaryId\":\"friend.testnet\""));
+        assert_eq!(TransferMessage::parse(&json).unwrap(), message);
+        assert_eq!(
+            TransferMessage::parse(&bid(None).to_json()).unwrap(),
+            bid(None)
+        );
+    }
+
+    #[test]
+    fn test_content_approval_bytes() {
+        let approval = ContentApproval {
+            contractId: "nft.testnet".parse().unwrap(),
+            creatorId: "creator.testnet".parse().unwrap(),
+            contentId: "a:b".into(),
+            timestamp: 1,
+            slotCount: 10,
+            slotPt: 2,
+            metadata: None,
+        };
+        assert_eq!(
+            String::from_utf8(approval.to_signed_bytes()).unwrap(),
+            r#"{"contractId":"nft.testnet","creatorId":"creator.testnet","contentId":"a:b","timestamp":1,"slotCount":10,"slotPt":2}"#
+        );
+    }
+
+    #[test]
+    fn test_parse_json_errors() {
+        let err = |msg: &str| TransferMessage::parse(msg).unwrap_err();
+        assert_eq!(
+            err(r#"{"op":"buy","creatorId":"a","contentId":"b","timestamp":1}"#),
+            "JSON message has no version"
+        );
+        assert_eq!(
+            err(r#"{"version":2,"op":"buy","creatorId":"a","contentId":"b","timestamp":1}"#),
+            "unsupported message version 2, expected 1"
+        );
+        assert!(err(r#"{"version":1,"op":"sell"}"#).starts_with("invalid JSON message"));
+        assert!(err(r#"{"version":1,"op":"buy","creatorId":"a"}"#).contains("contentId"));
+        assert!(err("{").starts_with("invalid JSON message"));
+        let msg = r#"{"version":1,"op":"buy","creatorId":"a","contentId":"b","timestamp":1,
+            "beneficiaryId":"Not Valid"}"#;
+        assert!(err(msg).starts_with("invalid JSON message"));
+    }
+}
@@ -0,0 +1,162 @@
+//! State versioning and the layouts of already deployed contracts, read once by `migrate`.
+//!
+//! The contract state is enveloped by a version number stored under its own key next to it.
+//! State without a version is V0. `migrate` upgrades any older layout to `STATE_VERSION`.
+//!
+//! V0 is the original layout: contents in a std `HashMap` stored inline in the contract
+//! state, f32 COTO bid values and a fixed block of `BIDPT_V0` slots of 1% per content.
+//! Its content and licence NFT collections were both created on the same storage prefixes.
+//!
+//! Content records carry their own version (`VersionedContentRec`) and are upgraded when read,
+//! the V0 records are moved into the contents map unchanged by `migrate`.
+
+use crate::*;
+
+pub const STATE_VERSION: u32 = 1;
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+pub fn state_version() -> u32 {
+    env::storage_read(STATE_VERSION_KEY).map_or(0, |bytes| {
+        u32::try_from_slice(&bytes).unwrap_or_else(|_| env::panic_str("Invalid state version"))
+    })
+}
+
+pub fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &STATE_VERSION.to_le_bytes());
+}
+
+//.rem  the owner, or the contract account itself for deploy-and-migrate in one transaction
+pub fn assert_migrator(owner_id: &AccountId) {
+    let caller = env::predecessor_account_id();
+    require!(
+        caller == *owner_id || caller == env::current_account_id(),
+        "Only the owner can migrate"
+    );
+}
+
+pub const BIDPT_V0: usize = 20;
+
+#[derive(Debug, BorshDeserialize, BorshSerialize)]
+pub struct ContentRecV0 {
+    pub creatorId: String,
+    pub contentId: String,
+    pub timestamp: u64,
+    pub bidvalArr: [f32; BIDPT_V0],
+    pub tokensArr: [usize; BIDPT_V0],
+    pub tokenId: usize,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV0 {
+    pub tokens: NonFungibleToken,
+    pub licenceTokens: NonFungibleToken,
+    pub lastTokenId: usize,
+    pub lastContentTokenId: usize,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub licenceMetadata: LazyOption<NFTContractMetadata>,
+    pub cnt: u32,
+    pub contents: HashMap<String, ContentRecV0>,
+    pub emptyContentRec: ContentRecV0,
+    pub lock: u32,
+}
+
+//.rem  V0 had no config, every bid was paid in the default COTO contract
+fn default_bid_token(bidvalArr: &[Amount]) -> Option<String> {
+    bidvalArr
+        .iter()
+        .any(|val| !val.is_zero())
+        .then(|| DEFAULT_COTO_ID.to_string())
+}
+
+impl ContentRecV0 {
+    //.rem  f32 values go through their decimal text form, so 22.5 stays exactly 22.5
+    pub fn into_current(self) -> ContentRec {
+        let bidvalArr = self
+            .bidvalArr
+            .iter()
+            .map(|val| {
+                Amount::parse_decimal(&val.to_string())
+                    .unwrap_or_else(|e| env::panic_str(&format!("migrate: {}", e)))
+            })
+            .collect::<Vec<Amount>>();
+        ContentRec {
+            bidToken: default_bid_token(&bidvalArr),
+            creatorId: self.creatorId,
+            contentId: self.contentId,
+            timestamp: self.timestamp,
+            slotPt: DEFAULT_SLOT_PT,
+            creatorPt: 100 - BIDPT_V0 as u32 * DEFAULT_SLOT_PT,
+            bidvalArr,
+            tokensArr: self.tokensArr.to_vec(),
+            tokenId: self.tokenId,
+            minIncrement: DEFAULT_MIN_INCREMENT,
+            minIncrementPt: DEFAULT_MIN_INCREMENT_PT,
+            ..ContentRec::default()
+        }
+    }
+}
+
+//.rem  a view of the V0 collections, good for the per-key maps only: the owner_by_id tree
+//.rem  was written by two instances and cannot be walked, but its values are stored per key
+pub fn shared_nft_collection(owner_id: AccountId) -> NonFungibleToken {
+    NonFungibleToken::new(
+        StorageKey::NonFungibleToken,
+        owner_id,
+        Some(StorageKey::TokenMetadata),
+        Some(StorageKey::Enumeration),
+        Some(StorageKey::Approval),
+    )
+}
+
+//.rem  moves `tokenIds` from the V0 shared collection into `to`, keeping owners, metadata and
+//.rem  approvals. The per-owner token sets are cleared first, `to` reuses their storage keys
+//.rem  (see Contract::new_licence_collection). Tokens already in `to` are skipped.
+pub fn split_nft_collection(
+    shared: &mut NonFungibleToken,
+    tokenIds: &[TokenId],
+    to: &mut NonFungibleToken,
+) {
+    let owned: Vec<(TokenId, AccountId)> = tokenIds
+        .iter()
+        .filter(|tokenId| to.owner_by_id.get(tokenId).is_none())
+        .filter_map(|tokenId| {
+            shared
+                .owner_by_id
+                .get(tokenId)
+                .map(|ownerId| (tokenId.clone(), ownerId))
+        })
+        .collect();
+
+    if let Some(tokensPerOwner) = shared.tokens_per_owner.as_mut() {
+        for (_, ownerId) in &owned {
+            if let Some(mut tokenSet) = tokensPerOwner.remove(ownerId) {
+                tokenSet.clear();
+            }
+        }
+    }
+
+    for (tokenId, ownerId) in owned {
+        let metadata = shared
+            .token_metadata_by_id
+            .as_mut()
+            .and_then(|byId| byId.remove(&tokenId));
+        to.internal_mint_with_refund(tokenId.clone(), ownerId, metadata, None);
+
+        let approvals = shared
+            .approvals_by_id
+            .as_mut()
+            .and_then(|byId| byId.remove(&tokenId));
+        let nextApprovalId = shared
+            .next_approval_id_by_id
+            .as_mut()
+            .and_then(|byId| byId.remove(&tokenId));
+        if let (Some(approvals), Some(byId)) = (approvals, to.approvals_by_id.as_mut()) {
+            byId.insert(&tokenId, &approvals);
+        }
+        if let (Some(nextApprovalId), Some(byId)) =
+            (nextApprovalId, to.next_approval_id_by_id.as_mut())
+        {
+            byId.insert(&tokenId, &nextApprovalId);
+        }
+    }
+}
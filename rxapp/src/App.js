@@ -3,14 +3,18 @@ import { useNearContext } from './NearContext'
 import { OwnerNfts } from './OwnerNfts'
 import './App.css'
 
-const bidPt = 10  // 10 or 20 percent buckets / slots (must be changed together w contract)
-const bidData = [ // bidding values for predefined buttons, can be anything
-  {value: bidPt * 1, maxPercent: bidPt / 2},
-  {value: bidPt * 2, maxPercent: bidPt / 3},
-  {value: 16.0, maxPercent: 2},
-  {value: 12.0, maxPercent: 3},
-  {value: 10.0, maxPercent: bidPt},
-  {value: 9.0, maxPercent: 4}
+// The slot layout (number of slots, slotPt) comes from the contract per content, the creator
+// picks it with register_content. Only registered content can be bid on: for anything else
+// get_bidding_state returns an empty record (no creatorId), so the samples below only get
+// bid buttons once their creators registered them.
+const defaultSlotPt = 1
+const bidData = [ // bidding values for predefined buttons (value for n slots), can be anything
+  {value: 10.0, slots: 5},
+  {value: 20.0, slots: 3},
+  {value: 16.0, slots: 2},
+  {value: 12.0, slots: 3},
+  {value: 10.0, slots: 10},
+  {value: 9.0, slots: 4}
 ]
 
 const contentData = [ // sample content data
@@ -22,10 +26,15 @@ const contentData = [ // sample content data
   ['85d491b3-18f8-40f6-be33-b83dd749a8a4', 'creator.testnet', 123367777]
 ]
 
-const ContentBiddingState = ({contentId, creatorId, timestamp}) => {
+// value in whole tokens (the buttons have at most 3 decimals) -> FT base units, as a string
+const toUnits = (value, decimals) =>
+  (BigInt(Math.round(value * 1000)) * 10n ** BigInt(decimals) / 1000n).toString()
+
+const ContentBiddingState = ({contentId, creatorId, timestamp, ftDecimals}) => {
   const [content, setContent] = useState({})
-  const {loggedIn, accountId, normalizeContentRec, biddingContract, ftContract} = useNearContext()
-  const {bidvalArr, tokensArr, normArr} = content
+  const {loggedIn, accountId, normalizeContentRec, yoctoToCoto, biddingContract, ftContract} = useNearContext()
+  const {bidvalArr = [], tokensArr, normArr, slotPt = defaultSlotPt} = content
+  const registered = !!content.creatorId
 
   useEffect(() => {
     let mounted = true
@@ -40,11 +49,13 @@ const ContentBiddingState = ({contentId, creatorId, timestamp}) => {
   }, [biddingContract, loggedIn, normalizeContentRec, contentId, creatorId, timestamp])
   
   const doBid = (value, maxPercent) => { // handler for bid buttons
-    const msg = `bid:${maxPercent}:${value}:${creatorId}:${contentId}:${timestamp}`
+    // the transferred amount is the bid, in base units of the FT (its decimals come from
+    // get_config); the unused part comes back
+    const msg = `bid:${creatorId}:${contentId}:${timestamp}:${maxPercent}`
     const bidPars = {
       receiver_id: biddingContract.contractId,
       msg,
-      amount: '' + value
+      amount: toUnits(value, ftDecimals)
     }
     console.log('Will call ft_transfer_call:', bidPars)
     ftContract?.ft_transfer_call(
@@ -72,20 +83,24 @@ const ContentBiddingState = ({contentId, creatorId, timestamp}) => {
             </div>
           )}
         </div>
-        {Array(bidPt).fill(0).map((_, ix) => 
+        {bidvalArr.map((bidval, ix) => 
           <div key={ix} className='bidPt shBox'>
-            <div className='pt'>1%</div>
-            <div className={`val ${bidvalArr?.[ix] ? '' : 'zero'}`}>{(bidvalArr?.[ix] || 0).toFixed(2)}$</div>
+            <div className='pt'>{slotPt}%</div>
+            <div className={`val ${yoctoToCoto(bidval) ? '' : 'zero'}`}>{yoctoToCoto(bidval).toFixed(2)}$</div>
             <div className='token'>{tokensArr?.[ix] || 0}</div>
           </div>
         )}
       </div>
       <div className='appContentRecRight'>
-        {bidData.map(({value, maxPercent}, ix) => 
-          <button className='bidButton' key={ix} onClick={() => doBid(value, ~~maxPercent)}>
-            {`${~~maxPercent}% for ${~~value}$`}
-          </button>
-        )}
+        {!registered
+          ? <div>Not registered</div>
+          : ftDecimals === undefined
+            ? <div>{`${ftContract?.contractId} is not accepted`}</div>
+            : bidData.map(({value, slots}, ix) => 
+                <button className='bidButton' key={ix} onClick={() => doBid(value, slots * slotPt)}>
+                  {`${slots * slotPt}% for ${~~value}$`}
+                </button>
+              )}
       </div>
     </div>
   )
@@ -112,7 +127,8 @@ const AppContent = ({children}) => <div className='appContent shBox'>{children}<
 
 export const App = () => {
   const [contractStatus, setContractStatus] = useState('')
-  const {loggedIn, biddingContract} = useNearContext()
+  const [ftDecimals, setFtDecimals] = useState()
+  const {loggedIn, biddingContract, ftContract} = useNearContext()
 
   useEffect(() => {
     let mounted = true
@@ -123,6 +139,17 @@ export const App = () => {
     return () => mounted = false  
   }, [loggedIn, biddingContract])
 
+  useEffect(() => { // the decimals of the FT we bid with, from its accepted token entry
+    let mounted = true
+    if (loggedIn && biddingContract && ftContract) {
+      biddingContract.get_config().then(({acceptedTokens}) => {
+        const accepted = acceptedTokens.find(({tokenId}) => tokenId === ftContract.contractId)
+        mounted && setFtDecimals(accepted?.decimals)
+      })
+    }
+    return () => mounted = false
+  }, [loggedIn, biddingContract, ftContract])
+
   return (
     <div className='App'>
       <AppHeader>
@@ -131,7 +158,7 @@ export const App = () => {
       {loggedIn
         ? <AppContent>
             {contentData.map(([contentId, creatorId, timestamp], ix) =>
-              <ContentBiddingState key={ix} {...{contentId, creatorId, timestamp}} />
+              <ContentBiddingState key={ix} {...{contentId, creatorId, timestamp, ftDecimals}} />
             )}
           </AppContent>
         : <>
@@ -52,7 +52,13 @@ const initContract = async () => {
       'get_nft_owners_for',
       'nft_tokens',
       'nft_tokens_for_owner',
-      'dash_get_contents'],
+      'dash_get_contents',
+      'get_earnings',
+      'quote_bid',
+      'quote_licence',
+      'get_config',
+      'get_paused',
+      'get_pending_settlements'],
     // Change methods can modify the state. But you don't receive the returned value when called.
     changeMethods: ['add_bid'],
   })
@@ -82,6 +88,9 @@ export const login = () => {
 
 // const clog = (...args) => console.log('🍄' + args.shift(), ...args)
 
+// Amounts come from the contract as yocto strings (24 decimals), we only display 3 decimals.
+const yoctoToCoto = yocto => Number(BigInt(yocto || 0) / 10n ** 21n) / 1000
+
 const shallowObjectEq = (objA, objB) => {
   if( typeof objA !== 'object') {
     return objA === objB
@@ -97,7 +106,7 @@ const shallowObjectEq = (objA, objB) => {
 
 const normalizeContentRec = async (rec, biddingContract) => {
   console.log('contentRec', rec)
-  const {creatorId, bidvalArr, tokensArr} = rec
+  const {creatorId, bidvalArr, tokensArr, slotPt} = rec
 
   if (creatorId) { // we have bids here
     const nftOwnersArr = await biddingContract.get_nft_owners_for({tokensArr})
@@ -107,8 +116,8 @@ const normalizeContentRec = async (rec, biddingContract) => {
     for (let i = 0; i < maxSlots; i++) {
       const owner = nftOwnersArr[i]
       hash[owner] || (hash[owner] = {sum: 0, pt: 0})
-      hash[owner].sum += bidvalArr[i]
-      hash[owner].pt++
+      hash[owner].sum += yoctoToCoto(bidvalArr[i])
+      hash[owner].pt += slotPt
     }
     rec.normArr = Object.entries(hash).map(([key, {sum, pt}]) => ({owner: key, sum, pt}))
     console.log('contentRec mod', rec)
@@ -122,7 +131,7 @@ export const useNearContext = () => useContext(NearContext)
 export const NearProvider = ({children}) => {
   const [nearState, setNearState] = useState({loggedIn: false, accountId: ''})
   const nearContext = {
-    ...nearState, login, logout, normalizeContentRec, 
+    ...nearState, login, logout, normalizeContentRec, yoctoToCoto,
     biddingContract: window.bidContract,
     ftContract: window.ftContract
   }